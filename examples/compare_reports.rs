@@ -0,0 +1,42 @@
+use main_game_loop::report::snapshot::ReportSnapshot;
+use std::process::ExitCode;
+
+//
+
+/// Compare two saved reports
+///
+/// `cargo run --example compare_reports -- baseline.report current.report [threshold]`
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (baseline, current, threshold) = match args.as_slice() {
+        [baseline, current] => (baseline, current, 0.1),
+        [baseline, current, threshold] => match threshold.parse() {
+            Ok(threshold) => (baseline, current, threshold),
+            Err(err) => {
+                eprintln!("invalid threshold: {err}");
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => {
+            eprintln!("usage: compare_reports <baseline> <current> [threshold]");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let load = |path: &str| {
+        ReportSnapshot::load(path).map_err(|err| eprintln!("failed to load {path}: {err}"))
+    };
+    let (Ok(baseline), Ok(current)) = (load(baseline), load(current)) else {
+        return ExitCode::FAILURE;
+    };
+
+    let comparison = current.compare(&baseline, threshold);
+    println!("{} -> {}", baseline.label, current.label);
+    print!("{}", comparison.to_colored_string());
+
+    if comparison.has_regressions() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
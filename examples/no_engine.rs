@@ -31,7 +31,7 @@ impl App {
         let update_loop = UpdateLoop::new(UpdateRate::PerSecond(60));
 
         let update_report = Reporter::new();
-        let frame_report = Reporter::new();
+        let frame_report = Reporter::new().with_budget_rate(UpdateRate::PerSecond(60));
         let event_report = Reporter::new();
 
         Self {
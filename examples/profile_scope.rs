@@ -0,0 +1,65 @@
+use main_game_loop::{
+    event::{Event, EventLoopTarget},
+    profile_scope,
+    report::registry::ReporterRegistry,
+    run_app,
+    state::window::WindowState,
+    update::{UpdateLoop, UpdateRate},
+};
+use std::time::Duration;
+use winit::{
+    event_loop::ControlFlow,
+    window::{Window, WindowBuilder},
+};
+
+//
+
+struct App {
+    _window: Window,
+    ws: WindowState,
+    update_loop: UpdateLoop,
+}
+
+fn init(target: &EventLoopTarget) -> App {
+    let _window = WindowBuilder::new().build(target).unwrap();
+    let ws = WindowState::new(&_window);
+    let update_loop = UpdateLoop::new(UpdateRate::PerSecond(60));
+
+    App {
+        _window,
+        ws,
+        update_loop,
+    }
+}
+
+fn event(app: &mut App, event: Event, _: &EventLoopTarget, control: &mut ControlFlow) {
+    profile_scope!("EVENT");
+    app.ws.event(&event);
+
+    if app.ws.should_close {
+        *control = ControlFlow::Exit;
+    }
+}
+
+fn draw(app: &mut App) {
+    app.update_loop.update(|| {
+        profile_scope!("UPDATE");
+        // update();
+    });
+
+    {
+        profile_scope!("FRAME");
+        std::thread::sleep(Duration::from_millis(3));
+        // draw();
+    }
+
+    let mut registry = ReporterRegistry::global();
+    if registry.should_report() {
+        log::debug!("\n{}", registry.report_all("3.0s"));
+    }
+}
+
+fn main() {
+    env_logger::init();
+    run_app!(init, event, draw);
+}
@@ -95,7 +95,7 @@ impl EventLoop {
                 // let event = InputState::deadzone(event, gilrs);
 
                 if let Some(event) = event {
-                    if let Err(..) = proxy.send_event(CustomEvent::GamePadEvent(event)) {
+                    if proxy.send_event(CustomEvent::GamePadEvent(event)).is_err() {
                         break;
                     }
                 };
@@ -1,8 +1,20 @@
 pub use crate::{
     event::*,
-    report::*,
+    report::{bench::*, folded::*, hitch::*, metric::*, registry::*, snapshot::*, sync::*, *},
     runnable::*,
-    state::{fpcam::*, gamepad::*, input::*, keyboard::*, window::*, *},
+    state::{
+        action::*, axis::*, capture::*, combo::*, fpcam::*, gamepad::*, input::*, input_event::*,
+        keyboard::*, mouse::*, position::*, text::*, tick::*, touch::*, window::*, *,
+    },
     update::*,
     *,
 };
+
+#[cfg(feature = "tracing")]
+pub use crate::report::trace::*;
+
+#[cfg(feature = "record")]
+pub use crate::state::record::*;
+
+#[cfg(feature = "openmetrics")]
+pub use crate::report::openmetrics::MetricsServer;
@@ -0,0 +1,433 @@
+use crate::runnable::Runnable;
+use instant::Instant;
+use std::{error::Error, fmt, time::Duration};
+
+//
+
+/// Runs a closure or a [`Runnable`] for a
+/// fixed number of frames and collects
+/// every sample for [`Budget`] checks
+///
+/// ```
+/// # use main_game_loop::report::bench::{Bench, Budget};
+/// # use std::time::Duration;
+/// let result = Bench::new(600).with_warm_up(60).run(|frame| {
+///     frame.time("UPDATE", || { /* update(); */ });
+///     frame.time("DRAW", || { /* draw(); */ });
+/// });
+///
+/// result.assert_budgets(&[
+///     Budget::percentile("FRAME", 95.0, Duration::from_millis(8)),
+///     Budget::mean("UPDATE", Duration::from_millis(1)),
+/// ]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bench {
+    frames: u32,
+    warm_up: u32,
+}
+
+/// Sections timed during a single frame
+#[derive(Debug, Default)]
+pub struct BenchFrame {
+    sections: Vec<(&'static str, Duration)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BenchResult {
+    sections: Vec<(&'static str, Samples)>,
+}
+
+/// Every sample of a single section, sorted
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Samples {
+    sorted: Vec<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Mean,
+    Min,
+    Max,
+
+    /// `Percentile(95.0)` is the p95
+    Percentile(f64),
+}
+
+/// `statistic` of `section` has to stay below `limit`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    pub section: &'static str,
+    pub statistic: Statistic,
+    pub limit: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetResult {
+    pub budget: Budget,
+
+    /// `None` if the section was never timed
+    pub actual: Option<Duration>,
+}
+
+/// Every checked [`Budget`] and how it went
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BudgetReport {
+    pub results: Vec<BudgetResult>,
+}
+
+//
+
+impl Bench {
+    /// The whole frame is reported as `FRAME`
+    pub const FRAME: &'static str = "FRAME";
+
+    pub fn new(frames: u32) -> Self {
+        Self { frames, warm_up: 0 }
+    }
+
+    /// Run `warm_up` frames before
+    /// the measured ones
+    pub fn with_warm_up(mut self, warm_up: u32) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    pub fn run<F>(&self, mut f: F) -> BenchResult
+    where
+        F: FnMut(&mut BenchFrame),
+    {
+        let mut result = BenchResult::default();
+        let mut frame = BenchFrame::default();
+
+        for i in 0..self.warm_up + self.frames {
+            let begin = Instant::now();
+            f(&mut frame);
+            let elapsed = begin.elapsed();
+
+            if i >= self.warm_up {
+                result.push(Self::FRAME, elapsed);
+                for &(section, elapsed) in frame.sections.iter() {
+                    result.push(section, elapsed);
+                }
+            }
+            frame.sections.clear();
+        }
+
+        result.sort();
+        result
+    }
+
+    /// Times [`Runnable::draw`] as `FRAME`
+    pub fn run_runnable<A>(&self, runnable: &mut A) -> BenchResult
+    where
+        A: Runnable,
+    {
+        self.run(|_| runnable.draw())
+    }
+}
+
+impl BenchFrame {
+    pub fn time<T, F>(&mut self, section: &'static str, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let begin = Instant::now();
+        let result = f();
+        self.record(section, begin.elapsed());
+        result
+    }
+
+    /// Sections timed multiple times
+    /// in a frame are summed
+    pub fn record(&mut self, section: &'static str, elapsed: Duration) {
+        match self.sections.iter_mut().find(|(s, _)| *s == section) {
+            Some((_, total)) => *total += elapsed,
+            None => self.sections.push((section, elapsed)),
+        }
+    }
+}
+
+impl BenchResult {
+    pub fn samples(&self, section: &str) -> Option<&Samples> {
+        self.sections
+            .iter()
+            .find(|(s, _)| *s == section)
+            .map(|(_, samples)| samples)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &Samples)> {
+        self.sections
+            .iter()
+            .map(|(section, samples)| (*section, samples))
+    }
+
+    pub fn check(&self, budgets: &[Budget]) -> BudgetReport {
+        let results = budgets
+            .iter()
+            .map(|&budget| BudgetResult {
+                budget,
+                actual: self
+                    .samples(budget.section)
+                    .and_then(|samples| samples.statistic(budget.statistic)),
+            })
+            .collect();
+
+        BudgetReport { results }
+    }
+
+    /// Panics with the [`BudgetReport`]
+    /// if any of the budgets failed
+    #[track_caller]
+    pub fn assert_budgets(&self, budgets: &[Budget]) {
+        let report = self.check(budgets);
+        if !report.passed() {
+            panic!("performance budgets failed:\n{report}");
+        }
+    }
+
+    /// mean, p95 and max of every section
+    pub fn report(&self) -> String {
+        let max_label_width = self
+            .sections
+            .iter()
+            .map(|(section, _)| section.len())
+            .max()
+            .unwrap_or(0);
+
+        self.sections
+            .iter()
+            .map(|(section, samples)| {
+                let padding = " ".repeat(max_label_width - section.len());
+                let stat = |statistic| {
+                    samples
+                        .statistic(statistic)
+                        .map(|d| format!("{d:.4?}"))
+                        .unwrap_or_else(|| "...".into())
+                };
+                format!(
+                    "{section}: {padding}mean {} p95 {} max {} ({} samples)\n",
+                    stat(Statistic::Mean),
+                    stat(Statistic::Percentile(95.0)),
+                    stat(Statistic::Max),
+                    samples.len(),
+                )
+            })
+            .collect()
+    }
+
+    fn push(&mut self, section: &'static str, elapsed: Duration) {
+        match self.sections.iter_mut().find(|(s, _)| *s == section) {
+            Some((_, samples)) => samples.sorted.push(elapsed),
+            None => self.sections.push((
+                section,
+                Samples {
+                    sorted: vec![elapsed],
+                },
+            )),
+        }
+    }
+
+    fn sort(&mut self) {
+        for (_, samples) in self.sections.iter_mut() {
+            samples.sorted.sort_unstable();
+        }
+    }
+}
+
+impl Samples {
+    pub fn len(&self) -> usize {
+        self.sorted.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sorted.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.sorted.iter().copied()
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        self.sorted
+            .iter()
+            .sum::<Duration>()
+            .checked_div(self.sorted.len() as u32)
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        self.sorted.first().copied()
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.sorted.last().copied()
+    }
+
+    /// Nearest rank percentile, `percentile` in range `0.0..=100.0`
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.sorted.len() as f64).ceil();
+        let i = (rank as usize).clamp(1, self.sorted.len().max(1)) - 1;
+        self.sorted.get(i).copied()
+    }
+
+    pub fn statistic(&self, statistic: Statistic) -> Option<Duration> {
+        match statistic {
+            Statistic::Mean => self.mean(),
+            Statistic::Min => self.min(),
+            Statistic::Max => self.max(),
+            Statistic::Percentile(percentile) => self.percentile(percentile),
+        }
+    }
+}
+
+impl fmt::Display for Statistic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Statistic::Mean => write!(f, "mean"),
+            Statistic::Min => write!(f, "min"),
+            Statistic::Max => write!(f, "max"),
+            Statistic::Percentile(percentile) => write!(f, "p{percentile}"),
+        }
+    }
+}
+
+impl Budget {
+    pub fn new(section: &'static str, statistic: Statistic, limit: Duration) -> Self {
+        Self {
+            section,
+            statistic,
+            limit,
+        }
+    }
+
+    pub fn mean(section: &'static str, limit: Duration) -> Self {
+        Self::new(section, Statistic::Mean, limit)
+    }
+
+    pub fn max(section: &'static str, limit: Duration) -> Self {
+        Self::new(section, Statistic::Max, limit)
+    }
+
+    pub fn percentile(section: &'static str, percentile: f64, limit: Duration) -> Self {
+        Self::new(section, Statistic::Percentile(percentile), limit)
+    }
+}
+
+impl BudgetResult {
+    pub fn passed(&self) -> bool {
+        self.actual.is_some_and(|actual| actual < self.budget.limit)
+    }
+}
+
+impl fmt::Display for BudgetResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Budget {
+            section,
+            statistic,
+            limit,
+        } = self.budget;
+        let status = if self.passed() { "ok" } else { "FAILED" };
+
+        match self.actual {
+            Some(actual) if actual >= limit => {
+                let over = actual - limit;
+                let percent = over.as_secs_f64() / limit.as_secs_f64() * 100.0;
+                write!(
+                    f,
+                    "{status}: {statistic} {section} {actual:.4?} >= {limit:.4?} (+{over:.4?}, +{percent:.1}%)"
+                )
+            }
+            Some(actual) => {
+                write!(
+                    f,
+                    "{status}: {statistic} {section} {actual:.4?} < {limit:.4?}"
+                )
+            }
+            None => write!(f, "{status}: {statistic} {section} was never timed"),
+        }
+    }
+}
+
+impl BudgetReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(BudgetResult::passed)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &BudgetResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+}
+
+impl fmt::Display for BudgetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in self.results.iter() {
+            writeln!(f, "{result}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for BudgetReport {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn from_ms(ms: impl IntoIterator<Item = u64>) -> Samples {
+        let mut sorted: Vec<_> = ms.into_iter().map(Duration::from_millis).collect();
+        sorted.sort_unstable();
+        Samples { sorted }
+    }
+
+    #[test]
+    fn percentile_nearest_rank() {
+        let samples = from_ms(1..=100);
+        assert_eq!(samples.percentile(0.0), Some(ms(1)));
+        assert_eq!(samples.percentile(50.0), Some(ms(50)));
+        assert_eq!(samples.percentile(95.0), Some(ms(95)));
+        assert_eq!(samples.percentile(99.5), Some(ms(100)));
+        assert_eq!(samples.percentile(100.0), Some(ms(100)));
+        assert_eq!(samples.percentile(250.0), Some(ms(100)));
+
+        let samples = from_ms([30, 10, 20]);
+        assert_eq!(samples.percentile(50.0), Some(ms(20)));
+        assert_eq!(samples.percentile(34.0), Some(ms(20)));
+        assert_eq!(samples.percentile(33.0), Some(ms(10)));
+    }
+
+    #[test]
+    fn empty_samples() {
+        let samples = Samples::default();
+        assert_eq!(samples.percentile(95.0), None);
+        assert_eq!(samples.mean(), None);
+        assert_eq!(samples.min(), None);
+    }
+
+    #[test]
+    fn budgets() {
+        let mut i = 0;
+        let result = Bench::new(10).with_warm_up(5).run(|frame| {
+            // the warm-up frames are the slow ones
+            frame.record("UPDATE", if i < 5 { ms(100) } else { ms(i) });
+            i += 1;
+        });
+
+        let update = result.samples("UPDATE").unwrap();
+        assert_eq!(update.len(), 10);
+        assert_eq!(update.max(), Some(ms(14)));
+        assert_eq!(result.samples(Bench::FRAME).map(Samples::len), Some(10));
+
+        let report = result.check(&[
+            Budget::max("UPDATE", ms(15)),
+            Budget::mean("UPDATE", ms(5)),
+            Budget::max("MISSING", ms(1)),
+        ]);
+        let passed: Vec<_> = report.results.iter().map(BudgetResult::passed).collect();
+        assert_eq!(passed, [true, false, false]);
+        assert_eq!(report.failed().count(), 2);
+    }
+}
@@ -0,0 +1,135 @@
+use rustc_hash::FxHashMap;
+use std::{fmt, io, time::Duration};
+
+//
+
+/// Aggregated self time of nested scopes
+///
+/// Written in the folded stack format
+/// used by `inferno` and `flamegraph.pl`:
+/// one `outer;inner;innermost <microseconds>`
+/// line per stack.
+///
+/// ```no_run
+/// # use main_game_loop::{profile_scope, report::registry::ReporterRegistry};
+/// fn frame() {
+///     profile_scope!("FRAME");
+///     {
+///         profile_scope!("UPDATE");
+///         // update();
+///     }
+///     // draw();
+/// }
+///
+/// frame();
+/// let file = std::fs::File::create("frame.folded").unwrap();
+/// ReporterRegistry::global().folded_stacks().write_folded(file).unwrap();
+/// // inferno-flamegraph frame.folded > frame.svg
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FoldedStacks {
+    stacks: FxHashMap<String, Duration>,
+}
+
+//
+
+impl FoldedStacks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `self_time` to `stack`
+    ///
+    /// `stack` is a `;` separated list
+    /// of scopes, outermost first, see
+    /// [`push_scope`]
+    pub fn add(&mut self, stack: &str, self_time: Duration) {
+        match self.stacks.get_mut(stack) {
+            Some(total) => *total += self_time,
+            None => {
+                self.stacks.insert(stack.to_string(), self_time);
+            }
+        }
+    }
+
+    pub fn get(&self, stack: &str) -> Option<Duration> {
+        self.stacks.get(stack).copied()
+    }
+
+    /// Stacks sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Duration)> {
+        let mut stacks: Vec<_> = self
+            .stacks
+            .iter()
+            .map(|(stack, self_time)| (stack.as_str(), *self_time))
+            .collect();
+        stacks.sort_unstable_by_key(|(stack, _)| *stack);
+        stacks.into_iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.stacks.clear();
+    }
+
+    pub fn write_folded<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        write!(writer, "{self}")
+    }
+}
+
+impl fmt::Display for FoldedStacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (stack, self_time) in self.iter() {
+            // a space separates the time
+            let stack = stack.replace(' ', "_");
+            writeln!(f, "{stack} {}", self_time.as_micros())?;
+        }
+        Ok(())
+    }
+}
+
+/// Append `scope` to a `;` separated `stack`
+///
+/// `;` and spaces in `scope` are replaced
+/// with `:` and `_`, they are separators
+/// in the folded stack format
+pub fn push_scope(stack: &mut String, scope: &str) {
+    if !stack.is_empty() {
+        stack.push(';');
+    }
+    stack.extend(scope.chars().map(|c| match c {
+        ';' => ':',
+        ' ' => '_',
+        c => c,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folded_format() {
+        let mut stack = String::new();
+        push_scope(&mut stack, "FRAME");
+        push_scope(&mut stack, "load;parse level");
+        assert_eq!(stack, "FRAME;load:parse_level");
+
+        let mut stacks = FoldedStacks::new();
+        stacks.add(&stack, Duration::from_micros(5));
+        stacks.add(&stack, Duration::from_micros(7));
+        stacks.add("FRAME", Duration::from_micros(3));
+        stacks.add("FRAME;two words", Duration::from_micros(1));
+
+        assert_eq!(
+            stacks.to_string(),
+            "FRAME 3\nFRAME;load:parse_level 12\nFRAME;two_words 1\n"
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use std::{collections::VecDeque, fmt, sync::Arc, time::Duration};
+
+//
+
+/// When is a sample considered a hitch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitchThreshold {
+    /// Longer than this
+    Fixed(Duration),
+
+    /// Longer than the running median
+    /// of recent samples times this
+    ///
+    /// Each section is also compared against
+    /// the running median of that section.
+    /// Factors that don't give a valid
+    /// [`Duration`] never detect anything.
+    MedianFactor(f32),
+}
+
+/// A single sample that exceeded its
+/// [`HitchThreshold`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hitch {
+    /// time the whole sample took
+    pub elapsed: Duration,
+
+    /// the limit it went over
+    pub threshold: Duration,
+
+    /// the section that went over its own
+    /// limit, `None` for the whole sample
+    pub section: Option<&'static str>,
+
+    /// sections recorded during the sample
+    pub sections: Vec<(&'static str, Duration)>,
+}
+
+pub type HitchCallback = Arc<dyn Fn(&Hitch) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct HitchDetector {
+    threshold: HitchThreshold,
+    history: History,
+    section_history: Vec<(&'static str, History)>,
+
+    /// reused for finding medians
+    scratch: Vec<Duration>,
+
+    sections: Vec<(&'static str, Duration)>,
+    callback: Option<HitchCallback>,
+    last: Option<Hitch>,
+    count: u32,
+}
+
+/// Recent samples and their median
+#[derive(Debug, Clone, Default)]
+struct History {
+    samples: VecDeque<Duration>,
+    median: Option<Duration>,
+
+    /// samples until the median is recomputed
+    stale_in: usize,
+}
+
+//
+
+impl Default for HitchThreshold {
+    fn default() -> Self {
+        Self::MedianFactor(2.0)
+    }
+}
+
+impl HitchThreshold {
+    fn limit(self, history: &History) -> Option<Duration> {
+        match self {
+            HitchThreshold::Fixed(limit) => Some(limit),
+            HitchThreshold::MedianFactor(factor) => {
+                let median = history.median?.as_secs_f32();
+                Duration::try_from_secs_f32(median * factor).ok()
+            }
+        }
+    }
+}
+
+impl History {
+    fn push(&mut self, elapsed: Duration, scratch: &mut Vec<Duration>) {
+        if self.samples.len() == HitchDetector::HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(elapsed);
+
+        if self.samples.len() < HitchDetector::MIN_HISTORY {
+            return;
+        }
+        if self.stale_in == 0 {
+            scratch.clear();
+            scratch.extend(self.samples.iter().copied());
+            let mid = scratch.len() / 2;
+            self.median = Some(*scratch.select_nth_unstable(mid).1);
+            self.stale_in = HitchDetector::MEDIAN_INTERVAL;
+        }
+        self.stale_in -= 1;
+    }
+}
+
+impl fmt::Display for Hitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(section) = self.section {
+            write!(f, "hitch in {section}: ")?;
+        } else {
+            write!(f, "hitch: ")?;
+        }
+        write!(f, "{:.4?} (threshold {:.4?})", self.elapsed, self.threshold)?;
+        for (label, elapsed) in self.sections.iter() {
+            write!(f, "\n  {label}: {elapsed:.4?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl HitchDetector {
+    /// number of samples the running median is taken from
+    pub const HISTORY: usize = 120;
+
+    /// [`HitchThreshold::MedianFactor`] stays quiet
+    /// until this many samples have been seen
+    pub const MIN_HISTORY: usize = 8;
+
+    /// samples between running median updates
+    pub const MEDIAN_INTERVAL: usize = 16;
+
+    pub fn new(threshold: HitchThreshold) -> Self {
+        Self {
+            threshold,
+            history: History::default(),
+            section_history: Vec::new(),
+            scratch: Vec::new(),
+            sections: Vec::new(),
+            callback: None,
+            last: None,
+            count: 0,
+        }
+    }
+
+    pub fn threshold(&self) -> HitchThreshold {
+        self.threshold
+    }
+
+    /// Called with every detected hitch
+    ///
+    /// Hitches are logged with `log::warn!`
+    /// when no callback is set
+    pub fn set_callback(&mut self, callback: Option<HitchCallback>) {
+        self.callback = callback;
+    }
+
+    /// The most recent hitch
+    pub fn last(&self) -> Option<&Hitch> {
+        self.last.as_ref()
+    }
+
+    /// Hitches detected since the last [`Self::reset_count`]
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn reset_count(&mut self) {
+        self.count = 0;
+    }
+
+    /// Record a section of the sample in progress
+    pub fn section(&mut self, label: &'static str, elapsed: Duration) {
+        match self.sections.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, total)) => *total += elapsed,
+            None => self.sections.push((label, elapsed)),
+        }
+    }
+
+    /// Forget the sections of the sample in progress
+    pub fn discard_sections(&mut self) {
+        self.sections.clear();
+    }
+
+    /// Finish the sample in progress
+    pub fn sample(&mut self, elapsed: Duration) {
+        let sections = std::mem::take(&mut self.sections);
+
+        let limit = self.threshold.limit(&self.history);
+        self.history.push(elapsed, &mut self.scratch);
+        if let Some(limit) = limit.filter(|limit| elapsed > *limit) {
+            self.report(elapsed, limit, None, &sections);
+            return;
+        }
+
+        // a fixed limit is only meaningful for whole samples
+        if let HitchThreshold::Fixed(_) = self.threshold {
+            return;
+        }
+        for &(label, elapsed) in sections.iter() {
+            let i = match self.section_history.iter().position(|(l, _)| *l == label) {
+                Some(i) => i,
+                None => {
+                    self.section_history.push((label, History::default()));
+                    self.section_history.len() - 1
+                }
+            };
+            let history = &mut self.section_history[i].1;
+            let limit = self.threshold.limit(history);
+            history.push(elapsed, &mut self.scratch);
+
+            if let Some(limit) = limit.filter(|limit| elapsed > *limit) {
+                self.report(elapsed, limit, Some(label), &sections);
+            }
+        }
+    }
+
+    fn report(
+        &mut self,
+        elapsed: Duration,
+        threshold: Duration,
+        section: Option<&'static str>,
+        sections: &[(&'static str, Duration)],
+    ) {
+        let hitch = Hitch {
+            elapsed,
+            threshold,
+            section,
+            sections: sections.to_vec(),
+        };
+        match &self.callback {
+            Some(callback) => callback(&hitch),
+            None => log::warn!("{hitch}"),
+        }
+        self.count += 1;
+        self.last = Some(hitch);
+    }
+}
+
+impl fmt::Debug for HitchDetector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HitchDetector")
+            .field("threshold", &self.threshold)
+            .field("history", &self.history.samples.len())
+            .field("sections", &self.sections)
+            .field("callback", &self.callback.is_some())
+            .field("last", &self.last)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn detector(threshold: HitchThreshold) -> HitchDetector {
+        let mut detector = HitchDetector::new(threshold);
+        detector.set_callback(Some(Arc::new(|_| {})));
+        detector
+    }
+
+    #[test]
+    fn median_factor() {
+        let mut detector = detector(HitchThreshold::MedianFactor(2.0));
+
+        // quiet until there is a median
+        detector.sample(ms(100));
+        for _ in 1..HitchDetector::MIN_HISTORY {
+            detector.sample(ms(10));
+        }
+        assert_eq!(detector.count(), 0);
+
+        detector.sample(ms(19));
+        assert_eq!(detector.count(), 0);
+        detector.sample(ms(25));
+        assert_eq!(detector.count(), 1);
+
+        let hitch = detector.last().unwrap();
+        assert_eq!(hitch.elapsed, ms(25));
+        assert_eq!(hitch.threshold, ms(20));
+        assert_eq!(hitch.section, None);
+    }
+
+    #[test]
+    fn median_follows_samples() {
+        let mut detector = detector(HitchThreshold::MedianFactor(2.0));
+        for _ in 0..HitchDetector::HISTORY {
+            detector.sample(ms(10));
+        }
+        for _ in 0..HitchDetector::HISTORY {
+            detector.sample(ms(30));
+        }
+        let count = detector.count();
+
+        // 50ms is no longer twice the median
+        detector.sample(ms(50));
+        assert_eq!(detector.count(), count);
+    }
+
+    #[test]
+    fn section_over_its_median() {
+        let mut detector = detector(HitchThreshold::MedianFactor(2.0));
+        for _ in 0..HitchDetector::MIN_HISTORY {
+            detector.section("UPDATE", ms(2));
+            detector.section("DRAW", ms(8));
+            detector.sample(ms(10));
+        }
+
+        // same frame time, but UPDATE tripled
+        detector.section("UPDATE", ms(6));
+        detector.section("DRAW", ms(4));
+        detector.sample(ms(10));
+
+        assert_eq!(detector.count(), 1);
+        let hitch = detector.last().unwrap();
+        assert_eq!(hitch.section, Some("UPDATE"));
+        assert_eq!(hitch.elapsed, ms(6));
+        assert_eq!(hitch.threshold, ms(4));
+        assert_eq!(hitch.sections, [("UPDATE", ms(6)), ("DRAW", ms(4))]);
+    }
+
+    #[test]
+    fn invalid_factor() {
+        for factor in [-1.0, f32::NAN, f32::INFINITY, 1e30] {
+            let mut detector = detector(HitchThreshold::MedianFactor(factor));
+            for _ in 0..HitchDetector::MIN_HISTORY * 2 {
+                detector.sample(ms(10));
+            }
+            detector.sample(ms(1000));
+            assert_eq!(detector.count(), 0, "{factor}");
+        }
+    }
+}
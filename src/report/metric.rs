@@ -0,0 +1,264 @@
+use instant::Instant;
+use std::time::Duration;
+
+//
+
+/// Monotonic counter reported as a
+/// total and a per second rate
+///
+/// Draw calls, received events,
+/// streamed bytes, ...
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Counter {
+    total: u64,
+    count: u64,
+    report_timer: Instant,
+    report_interval: Duration,
+
+    last_per_second: Option<f64>,
+}
+
+/// Value reported as the last,
+/// smallest and largest value
+/// seen in a report interval
+///
+/// Entities alive, memory used, ...
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Gauge {
+    value: Option<f64>,
+    min: f64,
+    max: f64,
+
+    last: Option<(f64, f64, f64)>,
+}
+
+//
+
+impl Counter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_with_interval(report_interval: Duration) -> Self {
+        Self {
+            total: 0,
+            count: 0,
+            report_timer: Instant::now(),
+            report_interval,
+
+            last_per_second: None,
+        }
+    }
+
+    #[inline]
+    pub fn add(&mut self, n: u64) {
+        self.total += n;
+        self.count += n;
+    }
+
+    #[inline]
+    pub fn incr(&mut self) {
+        self.add(1);
+    }
+
+    /// Everything counted so far
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn should_report(&self) -> bool {
+        self.report_timer.elapsed() >= self.report_interval
+    }
+
+    pub fn reset(&mut self) {
+        let per_second = self.count as f64 / self.report_interval.as_secs_f64();
+
+        self.count = 0;
+        self.report_timer = Instant::now();
+        self.last_per_second = Some(per_second);
+    }
+
+    pub fn last_per_second(&self) -> Option<f64> {
+        self.last_per_second
+    }
+
+    pub fn last_string(&self) -> (String, String) {
+        (
+            self.total.to_string(),
+            self.last_per_second
+                .map(|ps| format!("{ps:.1}"))
+                .unwrap_or_else(|| "...".into()),
+        )
+    }
+
+    /// Resets and reports `counters`
+    ///
+    /// See [`Reporter::report_all`](super::Reporter::report_all)
+    pub fn report_all<'a, I, L>(counters: I) -> String
+    where
+        I: IntoIterator<Item = (L, &'a mut Self)>,
+        L: AsRef<str>,
+    {
+        let rows = counters
+            .into_iter()
+            .map(|(label, counter)| {
+                counter.reset();
+                (label, counter.last_string())
+            })
+            .collect::<Vec<_>>();
+
+        table(("total", "per second"), " @ ", rows)
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self::new_with_interval(Duration::from_secs(3))
+    }
+}
+
+impl Gauge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, value: f64) {
+        match self.value {
+            Some(_) => {
+                self.min = self.min.min(value);
+                self.max = self.max.max(value);
+            }
+            None => {
+                self.min = value;
+                self.max = value;
+            }
+        }
+        self.value = Some(value);
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    /// Starts a new report interval
+    /// from the current value
+    pub fn reset(&mut self) {
+        if let Some(value) = self.value {
+            self.last = Some((value, self.min, self.max));
+            self.min = value;
+            self.max = value;
+        }
+    }
+
+    /// last, min and max values
+    /// of the last report interval
+    pub fn last(&self) -> Option<(f64, f64, f64)> {
+        self.last
+    }
+
+    pub fn last_string(&self) -> (String, String) {
+        match self.last {
+            Some((value, min, max)) => (value.to_string(), format!("{min}..{max}")),
+            None => ("...".into(), "...".into()),
+        }
+    }
+
+    /// Resets and reports `gauges`
+    ///
+    /// See [`Reporter::report_all`](super::Reporter::report_all)
+    pub fn report_all<'a, I, L>(gauges: I) -> String
+    where
+        I: IntoIterator<Item = (L, &'a mut Self)>,
+        L: AsRef<str>,
+    {
+        let rows = gauges
+            .into_iter()
+            .map(|(label, gauge)| {
+                gauge.reset();
+                let (value, range) = gauge.last_string();
+                (label, (value, format!("({range})")))
+            })
+            .collect::<Vec<_>>();
+
+        table(("last", "(min..max)"), " ", rows)
+    }
+}
+
+fn table<L>(header: (&str, &str), separator: &str, rows: Vec<(L, (String, String))>) -> String
+where
+    L: AsRef<str>,
+{
+    let max_label_width = rows
+        .iter()
+        .map(|(label, (value, _))| label.as_ref().len() + value.len())
+        .max()
+        .unwrap_or(0)
+        .max(header.0.len());
+    let padding = " ".repeat(max_label_width + 3 - header.0.len());
+    let first = format!("{padding}{}{separator}{}\n", header.0, header.1);
+
+    Some(first)
+        .into_iter()
+        .chain(rows.iter().map(|(label, (value, rest))| {
+            let label = label.as_ref();
+            let padding = " ".repeat(max_label_width - label.len() - value.len() + 1);
+            format!("{label}: {padding}{value}{separator}{rest}\n")
+        }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counter() {
+        let mut counter = Counter::new_with_interval(Duration::from_secs(2));
+        counter.add(5);
+        counter.incr();
+        assert_eq!(counter.total(), 6);
+        assert_eq!(counter.last_per_second(), None);
+
+        counter.reset();
+        assert_eq!(counter.last_per_second(), Some(3.0));
+        assert_eq!(counter.last_string(), ("6".into(), "3.0".into()));
+
+        // the total keeps counting, the rate starts over
+        counter.incr();
+        counter.reset();
+        assert_eq!(counter.total(), 7);
+        assert_eq!(counter.last_per_second(), Some(0.5));
+    }
+
+    #[test]
+    fn gauge() {
+        let mut gauge = Gauge::new();
+        gauge.reset();
+        assert_eq!(gauge.last(), None);
+
+        for value in [4.0, 1.0, 9.0, 3.0] {
+            gauge.set(value);
+        }
+        gauge.reset();
+        assert_eq!(gauge.last(), Some((3.0, 1.0, 9.0)));
+
+        // the next interval starts from the current value
+        gauge.set(5.0);
+        gauge.reset();
+        assert_eq!(gauge.last(), Some((5.0, 3.0, 5.0)));
+        assert_eq!(gauge.value(), Some(5.0));
+    }
+
+    #[test]
+    fn report_all() {
+        let mut spawned = Counter::new_with_interval(Duration::from_secs(1));
+        spawned.add(10);
+        let report = Counter::report_all([("spawned", &mut spawned)]);
+        assert!(report.contains("spawned:  10 @ 10.0\n"), "{report}");
+
+        let mut entities = Gauge::new();
+        entities.set(2.0);
+        let report = Gauge::report_all([("entities", &mut entities)]);
+        assert!(report.contains("entities:  2 (2..2)\n"), "{report}");
+    }
+}
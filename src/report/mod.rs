@@ -0,0 +1,720 @@
+use self::hitch::{Hitch, HitchCallback, HitchDetector, HitchThreshold};
+use crate::update::UpdateRate;
+use instant::Instant;
+use std::{
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::Arc,
+    time::Duration,
+};
+
+//
+
+pub mod bench;
+pub mod folded;
+pub mod hitch;
+pub mod metric;
+#[cfg(feature = "openmetrics")]
+pub mod openmetrics;
+pub mod registry;
+pub mod snapshot;
+pub mod sync;
+#[cfg(feature = "tracing")]
+pub mod trace;
+
+//
+
+/// target of the tracing spans emitted by reporters
+#[cfg(feature = "tracing")]
+pub(crate) const TRACING_TARGET: &str = module_path!();
+
+//
+
+/// What samples [`Reporter::reset`] reports on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ReportWindow {
+    /// Samples since the previous reset
+    #[default]
+    Tumbling,
+
+    /// Samples from the last `Duration`,
+    /// kept across resets
+    Rolling(Duration),
+}
+
+#[derive(Debug, Clone)]
+pub struct Reporter {
+    label: Option<&'static str>,
+
+    window: ReportWindow,
+    rolling: VecDeque<RollingSample>,
+    created: Instant,
+    warm_up_until: Option<Instant>,
+
+    count: u32,
+    elapsed: Duration,
+    report_timer: Instant,
+    report_interval: Duration,
+
+    last_interval: Option<Duration>,
+    last_per_second: Option<f64>,
+
+    budget: Option<Duration>,
+    over_budget: u32,
+    last_budget: Option<(f64, u32)>,
+
+    hitch: Option<HitchDetector>,
+    hitch_callback: HitchCallbackSlot,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Timer {
+    begin: Instant,
+}
+
+/// Times until dropped
+///
+/// Created by [`Reporter::scope`]
+#[derive(Debug)]
+#[must_use = "the guard records when dropped, binding it to `_` drops it immediately"]
+pub struct TimerGuard<'a> {
+    reporter: &'a mut Reporter,
+    timer: Option<Timer>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RollingSample {
+    end: Instant,
+    count: u32,
+    elapsed: Duration,
+    over_budget: u32,
+}
+
+/// Kept apart from the detector so the
+/// builder methods work in any order
+#[derive(Clone, Default)]
+struct HitchCallbackSlot(Option<HitchCallback>);
+
+//
+
+impl Reporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_with_interval(report_interval: Duration) -> Self {
+        Self {
+            label: None,
+
+            window: ReportWindow::Tumbling,
+            rolling: VecDeque::new(),
+            created: Instant::now(),
+            warm_up_until: None,
+
+            count: 0_u32,
+            elapsed: Duration::default(),
+            report_timer: Instant::now(),
+            report_interval,
+
+            last_interval: None,
+            last_per_second: None,
+
+            budget: None,
+            over_budget: 0,
+            last_budget: None,
+
+            hitch: None,
+            hitch_callback: HitchCallbackSlot::default(),
+        }
+    }
+
+    /// Name of this reporter in tracing spans
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+
+    /// Report on the samples of the last
+    /// `window` instead of the samples
+    /// since the previous reset
+    pub fn with_rolling_window(mut self, window: Duration) -> Self {
+        self.set_window(ReportWindow::Rolling(window));
+        self
+    }
+
+    pub fn set_window(&mut self, window: ReportWindow) {
+        self.window = window;
+        self.rolling.clear();
+    }
+
+    pub fn window(&self) -> ReportWindow {
+        self.window
+    }
+
+    /// Ignore every sample that ends
+    /// in the next `warm_up`
+    ///
+    /// Keeps startup hitches like shader
+    /// compilation out of the statistics
+    pub fn with_warm_up(mut self, warm_up: Duration) -> Self {
+        self.set_warm_up(warm_up);
+        self
+    }
+
+    pub fn set_warm_up(&mut self, warm_up: Duration) {
+        self.warm_up_until = Some(Instant::now() + warm_up);
+    }
+
+    pub fn is_warming_up(&self) -> bool {
+        self.warm_up_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Report the average sample as
+    /// a percentage of `budget` and count
+    /// samples that went over it
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.set_budget(Some(budget));
+        self
+    }
+
+    /// [`Self::with_budget`] with the interval
+    /// of an update or frame rate
+    ///
+    /// `UpdateRate::PerSecond(60)` gives a
+    /// budget of 16.6 ms
+    pub fn with_budget_rate(self, rate: UpdateRate) -> Self {
+        self.with_budget(rate.to_interval())
+    }
+
+    pub fn set_budget(&mut self, budget: Option<Duration>) {
+        self.budget = budget;
+        self.over_budget = 0;
+        self.last_budget = None;
+    }
+
+    pub fn budget(&self) -> Option<Duration> {
+        self.budget
+    }
+
+    /// Detect samples that take longer than `threshold`
+    ///
+    /// See [`Self::section`] for recording
+    /// what the offending sample was made of
+    pub fn with_hitch_detection(mut self, threshold: HitchThreshold) -> Self {
+        self.set_hitch_detection(Some(threshold));
+        self
+    }
+
+    /// Replace the default `log::warn!` hitch output
+    ///
+    /// Kept when hitch detection is
+    /// enabled or changed later
+    pub fn with_hitch_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&Hitch) + Send + Sync + 'static,
+    {
+        self.set_hitch_callback(Some(Arc::new(callback)));
+        self
+    }
+
+    pub fn set_hitch_callback(&mut self, callback: Option<HitchCallback>) {
+        if let Some(hitch) = self.hitch.as_mut() {
+            hitch.set_callback(callback.clone());
+        }
+        self.hitch_callback.0 = callback;
+    }
+
+    pub fn set_hitch_detection(&mut self, threshold: Option<HitchThreshold>) {
+        self.hitch = threshold.map(|threshold| {
+            let mut hitch = HitchDetector::new(threshold);
+            hitch.set_callback(self.hitch_callback.0.clone());
+            hitch
+        });
+    }
+
+    pub fn hitch_detector(&self) -> Option<&HitchDetector> {
+        self.hitch.as_ref()
+    }
+
+    pub fn hitch_detector_mut(&mut self) -> Option<&mut HitchDetector> {
+        self.hitch.as_mut()
+    }
+
+    /// Record a section of the sample currently being timed
+    ///
+    /// Sections are only kept when the sample
+    /// turns out to be a hitch. With
+    /// [`HitchThreshold::MedianFactor`] each
+    /// section is checked on its own too.
+    ///
+    /// ```no_run
+    /// # use main_game_loop::report::{Reporter, hitch::HitchThreshold};
+    /// # let mut frame_report = Reporter::new().with_hitch_detection(HitchThreshold::default());
+    /// let timer = frame_report.begin();
+    /// frame_report.time_section("UPDATE", || { /* update(); */ });
+    /// frame_report.time_section("DRAW", || { /* draw(); */ });
+    /// frame_report.end(timer);
+    /// ```
+    pub fn section(&mut self, label: &'static str, elapsed: Duration) {
+        if let Some(hitch) = self.hitch.as_mut() {
+            hitch.section(label, elapsed);
+        }
+    }
+
+    pub fn time_section<T, F>(&mut self, label: &'static str, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let timer = self.begin();
+        let result = f();
+        self.section(label, timer.elapsed());
+        result
+    }
+
+    pub fn begin(&self) -> Timer {
+        Timer {
+            begin: Instant::now(),
+        }
+    }
+
+    /// Times until the returned guard is dropped
+    ///
+    /// Samples aren't lost to early returns or `?`
+    ///
+    /// ```no_run
+    /// # use main_game_loop::report::Reporter;
+    /// fn load(report: &mut Reporter) -> std::io::Result<String> {
+    ///     let guard = report.scope();
+    ///     let config = std::fs::read_to_string("config.toml")?;
+    ///     if config.is_empty() {
+    ///         // don't count the fast path
+    ///         guard.cancel();
+    ///         return Ok(config);
+    ///     }
+    ///     Ok(config.to_uppercase())
+    /// }
+    /// ```
+    pub fn scope(&mut self) -> TimerGuard<'_> {
+        TimerGuard {
+            timer: Some(self.begin()),
+            reporter: self,
+        }
+    }
+
+    pub fn end(&mut self, timer: Timer) {
+        self.record(timer.elapsed());
+    }
+
+    /// Add a sample timed elsewhere
+    pub fn record(&mut self, elapsed: Duration) {
+        if self.is_warming_up() {
+            if let Some(hitch) = self.hitch.as_mut() {
+                hitch.discard_sections();
+            }
+            return;
+        }
+
+        let over_budget = self.budget.is_some_and(|budget| elapsed > budget);
+        self.elapsed += elapsed;
+        self.count += 1;
+        self.over_budget += over_budget as u32;
+        self.push_rolling(1, elapsed, over_budget as u32);
+
+        if let Some(hitch) = self.hitch.as_mut() {
+            hitch.sample(elapsed);
+        }
+    }
+
+    /// Time `f`
+    ///
+    /// With the `tracing` feature `f` also
+    /// runs inside a `reporter` trace span
+    pub fn time<T, F>(&mut self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            target: TRACING_TARGET,
+            "reporter",
+            label = self.label.unwrap_or_default()
+        )
+        .entered();
+
+        let timer = self.begin();
+        let result = f();
+        self.end(timer);
+        result
+    }
+
+    pub fn should_report(&mut self) -> bool {
+        self.report_timer.elapsed() >= self.report_interval
+    }
+
+    pub fn report_interval(&self) -> Duration {
+        self.report_interval
+    }
+
+    pub fn report_all<'a, I, L>(label: &str, reporters: I) -> String
+    where
+        I: IntoIterator<Item = (L, &'a mut Self)>,
+        L: AsRef<str>,
+    {
+        #[cfg(debug_assertions)]
+        const DEBUG: &str = "debug build";
+        #[cfg(not(debug_assertions))]
+        const DEBUG: &str = "release build";
+
+        let reporters: Vec<_> = reporters
+            .into_iter()
+            .map(|(label, reporter)| {
+                reporter.reset();
+                (label, reporter.last_string(), reporter.last_budget_string())
+            })
+            .collect();
+
+        let max_label_width = reporters
+            .iter()
+            .map(|(label, (_, time_per), _)| label.as_ref().len() + time_per.len())
+            .max()
+            .unwrap_or(0)
+            .max(7);
+        let padding = " ".repeat(max_label_width - 7);
+        let first = format!("Report {label} ({DEBUG})\n{padding}per second @ time per\n");
+
+        Some(first)
+            .into_iter()
+            .chain(reporters.iter().map(|(label, (int, per_sec), budget)| {
+                let label = label.as_ref();
+                let padding = " ".repeat(max_label_width - label.len() - per_sec.len() + 1);
+                match budget {
+                    Some(budget) => format!("{label}: {padding}{per_sec} @ {int} ({budget})\n"),
+                    None => format!("{label}: {padding}{per_sec} @ {int}\n"),
+                }
+            }))
+            .collect()
+    }
+
+    /// Add samples timed elsewhere
+    pub(crate) fn merge(&mut self, count: u32, elapsed: Duration) {
+        if self.is_warming_up() || count == 0 {
+            return;
+        }
+
+        self.count += count;
+        self.elapsed += elapsed;
+        self.push_rolling(count, elapsed, 0);
+    }
+
+    fn push_rolling(&mut self, count: u32, elapsed: Duration, over_budget: u32) {
+        let ReportWindow::Rolling(window) = self.window else {
+            return;
+        };
+
+        let end = Instant::now();
+        self.rolling.push_back(RollingSample {
+            end,
+            count,
+            elapsed,
+            over_budget,
+        });
+        while self
+            .rolling
+            .front()
+            .is_some_and(|sample| end.duration_since(sample.end) > window)
+        {
+            self.rolling.pop_front();
+        }
+    }
+
+    /// Starts a new report interval and
+    /// updates [`Self::last`] and [`Self::last_budget`]
+    pub fn reset(&mut self) {
+        let (count, elapsed, over_budget, span) = match self.window {
+            ReportWindow::Tumbling => (
+                self.count,
+                self.elapsed,
+                self.over_budget,
+                self.report_interval,
+            ),
+            ReportWindow::Rolling(window) => {
+                let now = Instant::now();
+                self.rolling
+                    .retain(|sample| now.duration_since(sample.end) <= window);
+
+                let (count, elapsed, over_budget) = self.rolling.iter().fold(
+                    (0, Duration::ZERO, 0),
+                    |(count, elapsed, over_budget), sample| {
+                        (
+                            count + sample.count,
+                            elapsed + sample.elapsed,
+                            over_budget + sample.over_budget,
+                        )
+                    },
+                );
+
+                // the window isn't full yet right after startup or warm-up
+                let since = self.warm_up_until.unwrap_or(self.created);
+                let span = window.min(now.saturating_duration_since(since));
+                (count, elapsed, over_budget, span)
+            }
+        };
+
+        let avg = elapsed.checked_div(count);
+        let fps = if span.is_zero() {
+            0.0
+        } else {
+            count as f64 / span.as_secs_f64()
+        };
+
+        self.count = 0;
+        self.elapsed = Duration::default();
+        self.over_budget = 0;
+        self.report_timer = Instant::now();
+        self.last_interval = avg;
+        self.last_per_second = Some(fps);
+
+        if let Some(budget) = self.budget {
+            let usage = avg.map_or(0.0, |avg| avg.as_secs_f64() / budget.as_secs_f64());
+            self.last_budget = Some((usage, over_budget));
+        }
+    }
+
+    pub fn last(&self) -> Option<(Duration, f64)> {
+        Some((self.last_interval?, self.last_per_second?))
+    }
+
+    /// Average sample as a fraction of the budget
+    /// and the number of samples over the budget
+    /// during the last report interval
+    pub fn last_budget(&self) -> Option<(f64, u32)> {
+        self.last_budget
+    }
+
+    /// `"73.0% of 16.6667ms, 2 over"`
+    pub fn last_budget_string(&self) -> Option<String> {
+        let budget = self.budget?;
+        let (usage, over) = self.last_budget?;
+        let usage = usage * 100.0;
+        Some(format!("{usage:.1}% of {budget:.4?}, {over} over"))
+    }
+
+    pub fn last_string(&self) -> (String, String) {
+        self.last_string_prec(4, 1)
+    }
+
+    pub fn last_string_prec(&self, int_prec: usize, per_sec_prec: usize) -> (String, String) {
+        (
+            self.last_interval
+                .map(|ft| format!("{ft:.int_prec$?}"))
+                .unwrap_or_else(|| "...".into()),
+            self.last_per_second
+                .map(|ps| format!("{ps:.per_sec_prec$}"))
+                .unwrap_or_else(|| "...".into()),
+        )
+    }
+}
+
+impl Default for Reporter {
+    fn default() -> Self {
+        Self::new_with_interval(Duration::from_secs(3))
+    }
+}
+
+impl fmt::Debug for HitchCallbackSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.is_some().fmt(f)
+    }
+}
+
+impl PartialEq for Reporter {
+    fn eq(&self, other: &Self) -> bool {
+        self.count == other.count
+            && self.elapsed == other.elapsed
+            && self.report_timer == other.report_timer
+            && self.report_interval == other.report_interval
+    }
+}
+
+impl Eq for Reporter {}
+
+impl Hash for Reporter {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.count.hash(state);
+        self.elapsed.hash(state);
+        self.report_timer.hash(state);
+        self.report_interval.hash(state);
+    }
+}
+
+impl TimerGuard<'_> {
+    pub fn elapsed(&self) -> Duration {
+        self.timer
+            .as_ref()
+            .map_or(Duration::ZERO, |timer| timer.elapsed())
+    }
+
+    /// See [`Reporter::section`]
+    pub fn section(&mut self, label: &'static str, elapsed: Duration) {
+        self.reporter.section(label, elapsed);
+    }
+
+    /// See [`Reporter::time_section`]
+    pub fn time_section<T, F>(&mut self, label: &'static str, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        self.reporter.time_section(label, f)
+    }
+
+    /// Drop without recording anything
+    pub fn cancel(mut self) {
+        self.timer = None;
+    }
+}
+
+impl Drop for TimerGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            self.reporter.end(timer);
+        }
+    }
+}
+
+impl Deref for Timer {
+    type Target = Instant;
+
+    fn deref(&self) -> &Self::Target {
+        &self.begin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn hitch_callback_before_detection() {
+        let hitches = Arc::new(AtomicU32::new(0));
+        let counter = hitches.clone();
+        let mut reporter = Reporter::new()
+            .with_hitch_callback(move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            })
+            .with_hitch_detection(HitchThreshold::Fixed(Duration::ZERO));
+
+        reporter.record(Duration::from_millis(1));
+        assert_eq!(hitches.load(Ordering::Relaxed), 1);
+
+        // replacing the detector keeps the callback
+        reporter.set_hitch_detection(Some(HitchThreshold::Fixed(Duration::ZERO)));
+        reporter.record(Duration::from_millis(1));
+        assert_eq!(hitches.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn warm_up_discards_sections() {
+        let mut reporter = Reporter::new()
+            .with_hitch_detection(HitchThreshold::Fixed(Duration::ZERO))
+            .with_hitch_callback(|_| {})
+            .with_warm_up(Duration::from_secs(3600));
+
+        for _ in 0..5 {
+            reporter.section("A", Duration::from_millis(1));
+            reporter.record(Duration::from_millis(1));
+        }
+        assert!(reporter.hitch_detector().unwrap().last().is_none());
+
+        reporter.warm_up_until = None;
+        reporter.section("A", Duration::from_millis(1));
+        reporter.record(Duration::from_millis(1));
+
+        let hitch = reporter.hitch_detector().unwrap().last().unwrap();
+        assert_eq!(hitch.sections, [("A", Duration::from_millis(1))]);
+    }
+
+    #[test]
+    fn budget_usage() {
+        let mut reporter = Reporter::new().with_budget(Duration::from_millis(10));
+        for ms in [5, 15, 10] {
+            reporter.record(Duration::from_millis(ms));
+        }
+        assert_eq!(reporter.last_budget(), None);
+
+        reporter.reset();
+        let (usage, over) = reporter.last_budget().unwrap();
+        assert!((usage - 1.0).abs() < 1e-9);
+        assert_eq!(over, 1);
+        assert_eq!(
+            reporter.last_budget_string().unwrap(),
+            "100.0% of 10.0000ms, 1 over"
+        );
+
+        // an empty interval uses none of the budget
+        reporter.reset();
+        assert_eq!(reporter.last_budget(), Some((0.0, 0)));
+    }
+
+    #[test]
+    fn rolling_and_tumbling_windows() {
+        let window = Duration::from_millis(50);
+        let mut tumbling = Reporter::new();
+        let mut rolling = Reporter::new().with_rolling_window(window);
+        let record = |ms, reporters: [&mut Reporter; 2]| {
+            for reporter in reporters {
+                reporter.record(Duration::from_millis(ms));
+            }
+        };
+
+        record(10, [&mut tumbling, &mut rolling]);
+        record(10, [&mut tumbling, &mut rolling]);
+        std::thread::sleep(window * 2);
+        record(40, [&mut tumbling, &mut rolling]);
+
+        tumbling.reset();
+        rolling.reset();
+        assert_eq!(tumbling.last().unwrap().0, Duration::from_millis(20));
+        assert_eq!(rolling.last().unwrap().0, Duration::from_millis(40));
+
+        // only the last sample is within the window
+        let (_, per_second) = rolling.last().unwrap();
+        assert!((per_second - 1.0 / window.as_secs_f64()).abs() < 1e-6);
+
+        // a tumbling window starts over, a rolling one keeps its samples
+        tumbling.reset();
+        rolling.reset();
+        assert_eq!(tumbling.last(), None);
+        assert_eq!(rolling.last().unwrap().0, Duration::from_millis(40));
+    }
+
+    #[test]
+    fn timer_guard() {
+        fn fallible(reporter: &mut Reporter, fail: bool) -> Result<(), ()> {
+            let _guard = reporter.scope();
+            if fail {
+                Err(())?;
+            }
+            Ok(())
+        }
+
+        let mut reporter = Reporter::new();
+        drop(reporter.scope());
+        assert_eq!(reporter.count, 1);
+
+        // early returns are still recorded
+        assert!(fallible(&mut reporter, true).is_err());
+        assert!(fallible(&mut reporter, false).is_ok());
+        assert_eq!(reporter.count, 3);
+
+        reporter.scope().cancel();
+        assert_eq!(reporter.count, 3);
+    }
+}
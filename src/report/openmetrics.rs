@@ -0,0 +1,371 @@
+use super::registry::ReporterRegistry;
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+//
+
+/// Serves a [`ReporterRegistry`] in the
+/// OpenMetrics text format at `/metrics`
+///
+/// Stops when dropped.
+///
+/// ```no_run
+/// # use main_game_loop::report::openmetrics::MetricsServer;
+/// let server = MetricsServer::bind("127.0.0.1:9184").unwrap();
+/// log::info!("metrics at http://{}/metrics", server.local_addr());
+/// ```
+#[derive(Debug)]
+pub struct MetricsServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+//
+
+/// How long a client gets to send its
+/// request and read the response, clients
+/// are served one at a time
+const CLIENT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How often the server checks for
+/// new clients and for being stopped
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Content type of [`encode`]d metrics
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Encode the last report of every reporter,
+/// the current value of every counter and
+/// gauge and the [`UpdateLoop`](crate::update::UpdateLoop)
+/// set with [`ReporterRegistry::set_update_loop`]
+/// in the OpenMetrics text format
+///
+/// Doesn't reset anything, reporters only
+/// change after [`ReporterRegistry::report_all`]
+pub fn encode(registry: &ReporterRegistry) -> String {
+    let mut out = String::new();
+
+    family(
+        &mut out,
+        ("main_game_loop_time_per_sample_seconds", "gauge"),
+        "Average time per sample in the last report",
+        "section",
+        registry.iter().filter_map(|(section, reporter)| {
+            let (time_per, _) = reporter.last()?;
+            Some((section, time_per.as_secs_f64()))
+        }),
+    );
+    family(
+        &mut out,
+        ("main_game_loop_samples_per_second", "gauge"),
+        "Samples per second in the last report",
+        "section",
+        registry.iter().filter_map(|(section, reporter)| {
+            let (_, per_second) = reporter.last()?;
+            Some((section, per_second))
+        }),
+    );
+    family(
+        &mut out,
+        ("main_game_loop_budget_usage_ratio", "gauge"),
+        "Average sample as a fraction of its budget in the last report",
+        "section",
+        registry.iter().filter_map(|(section, reporter)| {
+            let (usage, _) = reporter.last_budget()?;
+            Some((section, usage))
+        }),
+    );
+    family(
+        &mut out,
+        ("main_game_loop_over_budget_samples", "gauge"),
+        "Samples over budget in the last report",
+        "section",
+        registry.iter().filter_map(|(section, reporter)| {
+            let (_, over) = reporter.last_budget()?;
+            Some((section, over as f64))
+        }),
+    );
+    family(
+        &mut out,
+        ("main_game_loop_counter", "counter"),
+        "Registry counters",
+        "name",
+        registry
+            .iter_counters()
+            .map(|(name, counter)| (name, counter.total() as f64)),
+    );
+    family(
+        &mut out,
+        ("main_game_loop_gauge", "gauge"),
+        "Registry gauges",
+        "name",
+        registry
+            .iter_gauges()
+            .filter_map(|(name, gauge)| Some((name, gauge.value()?))),
+    );
+
+    if let Some(update_loop) = registry.update_loop() {
+        let stats = update_loop.stats();
+        let single = |out: &mut String, name_ty, help, sample| {
+            family(out, name_ty, help, "", [("", sample)]);
+        };
+
+        single(
+            &mut out,
+            ("main_game_loop_update_rate_hertz", "gauge"),
+            "Requested fixed update rate",
+            1.0 / update_loop.interval().as_secs_f64(),
+        );
+        single(
+            &mut out,
+            ("main_game_loop_updates", "counter"),
+            "Fixed updates run",
+            stats.updates as f64,
+        );
+        single(
+            &mut out,
+            ("main_game_loop_update_frames", "counter"),
+            "Frames that ran the update loop",
+            stats.frames as f64,
+        );
+        single(
+            &mut out,
+            ("main_game_loop_update_idle_frames", "counter"),
+            "Frames with no updates",
+            stats.idle_frames as f64,
+        );
+        single(
+            &mut out,
+            ("main_game_loop_update_catch_up_frames", "counter"),
+            "Frames with more than one update",
+            stats.catch_up_frames as f64,
+        );
+        single(
+            &mut out,
+            ("main_game_loop_max_updates_per_frame", "gauge"),
+            "Most updates run in a single frame",
+            stats.max_updates_per_frame as f64,
+        );
+        single(
+            &mut out,
+            ("main_game_loop_update_lag_ratio", "gauge"),
+            "Time until the next update as a fraction of the interval",
+            update_loop.delta() as f64,
+        );
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+fn family<'a, I>(out: &mut String, (name, ty): (&str, &str), help: &str, label: &str, samples: I)
+where
+    I: IntoIterator<Item = (&'a str, f64)>,
+{
+    let _ = writeln!(out, "# TYPE {name} {ty}");
+    let _ = writeln!(out, "# HELP {name} {help}");
+
+    // counter samples are suffixed with `_total`
+    let suffix = if ty == "counter" { "_total" } else { "" };
+    for (value, sample) in samples {
+        if label.is_empty() {
+            let _ = writeln!(out, "{name}{suffix} {sample}");
+            continue;
+        }
+
+        let value = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        let _ = writeln!(out, "{name}{suffix}{{{label}=\"{value}\"}} {sample}");
+    }
+}
+
+impl MetricsServer {
+    /// Serve [`ReporterRegistry::global`]
+    ///
+    /// Bind to port `0` to let the
+    /// OS pick a free port
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::start(addr, None)
+    }
+
+    /// Serve `registry` instead of the global one
+    pub fn bind_with_registry(
+        addr: impl ToSocketAddrs,
+        registry: Arc<Mutex<ReporterRegistry>>,
+    ) -> io::Result<Self> {
+        Self::start(addr, Some(registry))
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn start(
+        addr: impl ToSocketAddrs,
+        registry: Option<Arc<Mutex<ReporterRegistry>>>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        // polled, so stopping doesn't
+        // depend on a client connecting
+        listener.set_nonblocking(true)?;
+
+        let thread = {
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("metrics server".into())
+                .spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        let stream = match listener.accept() {
+                            Ok((stream, _)) => stream,
+                            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                                thread::sleep(ACCEPT_INTERVAL);
+                                continue;
+                            }
+                            Err(err) => {
+                                log::warn!("Failed to accept a metrics client: {err}");
+                                thread::sleep(ACCEPT_INTERVAL);
+                                continue;
+                            }
+                        };
+
+                        if let Err(err) = serve(stream, registry.as_deref()) {
+                            log::warn!("Failed to serve metrics: {err}");
+                        }
+                    }
+                })?
+        };
+
+        Ok(Self {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(mut stream: TcpStream, registry: Option<&Mutex<ReporterRegistry>>) -> io::Result<()> {
+    // might inherit non-blocking from the listener
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // skip the headers
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = match registry {
+                Some(registry) => encode(&registry.lock().unwrap_or_else(PoisonError::into_inner)),
+                None => encode(&ReporterRegistry::global()),
+            };
+            ("200 OK", CONTENT_TYPE, body)
+        }
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Read, time::Instant};
+
+    fn scrape(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn scrape_metrics() {
+        let mut registry = ReporterRegistry::new();
+        registry.counter("spawned").add(3);
+        registry.gauge("entities").set(12.0);
+        registry.set_update_loop(&Default::default());
+        let registry = Arc::new(Mutex::new(registry));
+
+        let server = MetricsServer::bind_with_registry("127.0.0.1:0", registry).unwrap();
+        let response = scrape(server.local_addr(), "/metrics");
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        assert!(head.contains(CONTENT_TYPE));
+        assert!(body.contains("main_game_loop_counter_total{name=\"spawned\"} 3\n"));
+        assert!(body.contains("main_game_loop_gauge{name=\"entities\"} 12\n"));
+        assert!(body.contains("main_game_loop_update_rate_hertz "));
+        assert!(body.contains("main_game_loop_updates_total 0\n"));
+        assert!(body.ends_with("# EOF\n"));
+
+        let response = scrape(server.local_addr(), "/other");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    }
+
+    #[test]
+    fn idle_client_does_not_block() {
+        let registry = Arc::new(Mutex::new(ReporterRegistry::new()));
+        let server = MetricsServer::bind_with_registry("127.0.0.1:0", registry).unwrap();
+
+        let _idle = TcpStream::connect(server.local_addr()).unwrap();
+        let response = scrape(server.local_addr(), "/metrics");
+        assert!(response.ends_with("# EOF\n"));
+
+        let _idle = TcpStream::connect(server.local_addr()).unwrap();
+        let begin = Instant::now();
+        drop(server);
+        assert!(begin.elapsed() < CLIENT_TIMEOUT * 4);
+    }
+
+    #[test]
+    fn stops_without_clients() {
+        let registry = Arc::new(Mutex::new(ReporterRegistry::new()));
+        let server = MetricsServer::bind_with_registry("0.0.0.0:0", registry).unwrap();
+        let addr = server.local_addr();
+
+        let begin = Instant::now();
+        drop(server);
+        assert!(begin.elapsed() < ACCEPT_INTERVAL * 4);
+
+        // the port is free again
+        TcpListener::bind(addr).unwrap();
+    }
+}
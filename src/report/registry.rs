@@ -0,0 +1,508 @@
+use super::{
+    folded::{push_scope, FoldedStacks},
+    metric::{Counter, Gauge},
+    Reporter, Timer,
+};
+use crate::update::UpdateLoop;
+use instant::Instant;
+use rustc_hash::FxHashMap;
+use std::{
+    cell::RefCell,
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError, TryLockError},
+    time::Duration,
+};
+
+//
+
+/// Collection of named [`Reporter`]s,
+/// [`Counter`]s and [`Gauge`]s
+///
+/// Metrics are created on first use
+/// and reported in the order they were
+/// created in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReporterRegistry {
+    reporters: Named<Reporter>,
+    counters: Named<Counter>,
+    gauges: Named<Gauge>,
+    stacks: FoldedStacks,
+    update_loop: Option<UpdateLoop>,
+    report_interval: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Named<T> {
+    items: Vec<(&'static str, T)>,
+    index: FxHashMap<&'static str, usize>,
+}
+
+/// Times the scope it lives in
+///
+/// Created by [`profile_scope!`](crate::profile_scope)
+/// and recorded into the global
+/// [`ReporterRegistry`] when dropped, or
+/// queued if the registry is locked then.
+///
+/// Scopes nest per thread and have to
+/// be dropped in the reverse order
+/// they were created in.
+#[derive(Debug)]
+pub struct ScopeTimer {
+    name: &'static str,
+    timer: Timer,
+
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+//
+
+struct ScopeFrame {
+    name: &'static str,
+    children: Duration,
+}
+
+/// Sample waiting for the global registry
+struct PendingSample {
+    name: &'static str,
+    elapsed: Duration,
+
+    /// folded stack path and self time
+    stack: Option<(String, Duration)>,
+}
+
+//
+
+static GLOBAL: OnceLock<Mutex<ReporterRegistry>> = OnceLock::new();
+
+/// Samples that ended while [`GLOBAL`] was
+/// locked, so ending a scope never waits
+/// for it. Merged by the next sample or
+/// [`ReporterRegistry::global`].
+static PENDING: Mutex<Vec<PendingSample>> = Mutex::new(Vec::new());
+
+/// Samples beyond this are dropped
+/// while the registry stays locked
+const MAX_PENDING: usize = 4096;
+
+thread_local! {
+    static SCOPE_STACK: RefCell<Vec<ScopeFrame>> = const { RefCell::new(Vec::new()) };
+
+    /// reused folded stack path
+    static SCOPE_PATH: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+//
+
+impl ReporterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Metrics created by this
+    /// registry use `report_interval`
+    pub fn new_with_interval(report_interval: Duration) -> Self {
+        Self {
+            reporters: Named::default(),
+            counters: Named::default(),
+            gauges: Named::default(),
+            stacks: FoldedStacks::default(),
+            update_loop: None,
+            report_interval,
+        }
+    }
+
+    /// The registry used by
+    /// [`profile_scope!`](crate::profile_scope)
+    ///
+    /// Scopes end into a buffer that is
+    /// merged here, so they can end while
+    /// the returned guard is held. Hitch
+    /// callbacks run during the merge and
+    /// must not call this.
+    pub fn global() -> MutexGuard<'static, Self> {
+        let mut registry = GLOBAL
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        registry.merge_pending();
+        registry
+    }
+
+    /// Record into [`Self::global`] without
+    /// waiting for it to be unlocked
+    pub fn record_global(name: &'static str, elapsed: Duration) {
+        record_or_queue(name, elapsed, None);
+    }
+
+    fn merge_pending(&mut self) {
+        let pending = std::mem::take(&mut *PENDING.lock().unwrap_or_else(PoisonError::into_inner));
+        for sample in pending {
+            self.record(sample.name, sample.elapsed);
+            if let Some((path, self_time)) = sample.stack {
+                self.stacks.add(&path, self_time);
+            }
+        }
+    }
+
+    /// Get the reporter named `name`
+    /// or create it if it doesn't exist yet
+    pub fn reporter(&mut self, name: &'static str) -> &mut Reporter {
+        let report_interval = self.report_interval;
+        self.reporters.get_or_insert_with(name, || {
+            Reporter::new_with_interval(report_interval).with_label(name)
+        })
+    }
+
+    /// Get the counter named `name`
+    /// or create it if it doesn't exist yet
+    pub fn counter(&mut self, name: &'static str) -> &mut Counter {
+        let report_interval = self.report_interval;
+        self.counters
+            .get_or_insert_with(name, || Counter::new_with_interval(report_interval))
+    }
+
+    /// Get the gauge named `name`
+    /// or create it if it doesn't exist yet
+    pub fn gauge(&mut self, name: &'static str) -> &mut Gauge {
+        self.gauges.get_or_insert_with(name, Gauge::new)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Reporter> {
+        self.reporters.get(name)
+    }
+
+    pub fn get_counter(&self, name: &str) -> Option<&Counter> {
+        self.counters.get(name)
+    }
+
+    pub fn get_gauge(&self, name: &str) -> Option<&Gauge> {
+        self.gauges.get(name)
+    }
+
+    /// Self time of nested
+    /// [`profile_scope!`](crate::profile_scope)s
+    pub fn folded_stacks(&self) -> &FoldedStacks {
+        &self.stacks
+    }
+
+    pub fn folded_stacks_mut(&mut self) -> &mut FoldedStacks {
+        &mut self.stacks
+    }
+
+    /// Keep a copy of `update_loop` for its
+    /// rate and [`UpdateStats`](crate::update::UpdateStats)
+    ///
+    /// Call it once per frame after
+    /// the updates to keep it current
+    pub fn set_update_loop(&mut self, update_loop: &UpdateLoop) {
+        self.update_loop = Some(*update_loop);
+    }
+
+    pub fn update_loop(&self) -> Option<&UpdateLoop> {
+        self.update_loop.as_ref()
+    }
+
+    pub fn time<T, F>(&mut self, name: &'static str, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        self.reporter(name).time(f)
+    }
+
+    pub fn end(&mut self, name: &'static str, timer: Timer) {
+        self.reporter(name).end(timer);
+    }
+
+    /// Add a sample timed elsewhere
+    pub fn record(&mut self, name: &'static str, elapsed: Duration) {
+        self.reporter(name).record(elapsed);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &Reporter)> {
+        self.reporters.iter()
+    }
+
+    pub fn iter_counters(&self) -> impl Iterator<Item = (&'static str, &Counter)> {
+        self.counters.iter()
+    }
+
+    pub fn iter_gauges(&self) -> impl Iterator<Item = (&'static str, &Gauge)> {
+        self.gauges.iter()
+    }
+
+    pub fn should_report(&mut self) -> bool {
+        self.reporters
+            .items
+            .iter_mut()
+            .any(|(_, reporter)| reporter.should_report())
+            || self
+                .counters
+                .items
+                .iter()
+                .any(|(_, counter)| counter.should_report())
+    }
+
+    pub fn report_interval(&self) -> Duration {
+        self.report_interval
+    }
+
+    /// Resets and reports every registered
+    /// reporter, counter and gauge
+    ///
+    /// See [`Reporter::report_all`]
+    pub fn report_all(&mut self, label: &str) -> String {
+        let mut report = Reporter::report_all(label, self.reporters.iter_mut());
+        if !self.counters.items.is_empty() {
+            report.push_str(&Counter::report_all(self.counters.iter_mut()));
+        }
+        if !self.gauges.items.is_empty() {
+            report.push_str(&Gauge::report_all(self.gauges.iter_mut()));
+        }
+        report
+    }
+
+    pub fn clear(&mut self) {
+        self.reporters = Named::default();
+        self.counters = Named::default();
+        self.gauges = Named::default();
+        self.stacks.clear();
+        self.update_loop = None;
+    }
+}
+
+impl Default for ReporterRegistry {
+    fn default() -> Self {
+        Self::new_with_interval(Reporter::default().report_interval())
+    }
+}
+
+impl<T> Default for Named<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            index: FxHashMap::default(),
+        }
+    }
+}
+
+impl<T> Named<T> {
+    fn get_or_insert_with<F>(&mut self, name: &'static str, f: F) -> &mut T
+    where
+        F: FnOnce() -> T,
+    {
+        let i = match self.index.get(name) {
+            Some(&i) => i,
+            None => {
+                let i = self.items.len();
+                self.items.push((name, f()));
+                self.index.insert(name, i);
+                i
+            }
+        };
+
+        &mut self.items[i].1
+    }
+
+    fn get(&self, name: &str) -> Option<&T> {
+        self.index.get(name).map(|&i| &self.items[i].1)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&'static str, &T)> {
+        self.items.iter().map(|(name, item)| (*name, item))
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (&'static str, &mut T)> {
+        self.items.iter_mut().map(|(name, item)| (*name, item))
+    }
+}
+
+impl ScopeTimer {
+    pub fn new(name: &'static str) -> Self {
+        SCOPE_STACK.with(|stack| {
+            stack.borrow_mut().push(ScopeFrame {
+                name,
+                children: Duration::ZERO,
+            })
+        });
+
+        Self {
+            name,
+            timer: Timer {
+                begin: Instant::now(),
+            },
+
+            #[cfg(feature = "tracing")]
+            _span: tracing::trace_span!(target: super::TRACING_TARGET, "scope", label = name)
+                .entered(),
+        }
+    }
+}
+
+impl Drop for ScopeTimer {
+    fn drop(&mut self) {
+        let elapsed = self.timer.elapsed();
+
+        SCOPE_STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let frame = stack.pop();
+            let children = frame.map_or(Duration::ZERO, |frame| frame.children);
+            if let Some(parent) = stack.last_mut() {
+                parent.children += elapsed;
+            }
+
+            SCOPE_PATH.with(|path| {
+                let mut path = path.borrow_mut();
+                path.clear();
+                for frame in stack.iter() {
+                    push_scope(&mut path, frame.name);
+                }
+                push_scope(&mut path, self.name);
+
+                let self_time = elapsed.saturating_sub(children);
+                record_or_queue(self.name, elapsed, Some((&path, self_time)));
+            });
+        });
+    }
+}
+
+/// Record right away if [`GLOBAL`]
+/// is free, queue the sample if not
+fn record_or_queue(name: &'static str, elapsed: Duration, stack: Option<(&str, Duration)>) {
+    let mut registry = match GLOBAL.get_or_init(Default::default).try_lock() {
+        Ok(registry) => registry,
+        Err(TryLockError::Poisoned(err)) => err.into_inner(),
+        Err(TryLockError::WouldBlock) => {
+            let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+            if pending.len() < MAX_PENDING {
+                pending.push(PendingSample {
+                    name,
+                    elapsed,
+                    stack: stack.map(|(path, self_time)| (path.to_string(), self_time)),
+                });
+            }
+            return;
+        }
+    };
+
+    registry.merge_pending();
+    registry.record(name, elapsed);
+    if let Some((path, self_time)) = stack {
+        registry.stacks.add(path, self_time);
+    }
+}
+
+/// Times the rest of the enclosing block
+/// into the global [`ReporterRegistry`]
+///
+/// With the `tracing` feature the block
+/// also runs inside a `scope` trace span
+///
+/// ```no_run
+/// # use main_game_loop::{profile_scope, report::registry::ReporterRegistry};
+/// fn update() {
+///     profile_scope!("UPDATE");
+///     // ..
+/// }
+///
+/// update();
+/// log::debug!("\n{}", ReporterRegistry::global().report_all("frame"));
+/// ```
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::report::registry::ScopeTimer::new($name);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// tests sharing [`GLOBAL`] run one at a time
+    static GLOBAL_TESTS: Mutex<()> = Mutex::new(());
+
+    fn serial() -> MutexGuard<'static, ()> {
+        GLOBAL_TESTS.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[test]
+    fn scope_ends_while_global_is_held() {
+        let _serial = serial();
+        let registry = ReporterRegistry::global();
+        {
+            crate::profile_scope!("scope_ends_while_global_is_held");
+        }
+        assert_eq!(PENDING.lock().unwrap().len(), 1);
+        drop(registry);
+
+        let registry = ReporterRegistry::global();
+        let reporter = registry.get("scope_ends_while_global_is_held").unwrap();
+        assert_eq!(reporter.count, 1);
+    }
+
+    #[test]
+    fn scope_records_on_drop() {
+        let _serial = serial();
+        let registry = ReporterRegistry::global();
+        ReporterRegistry::record_global("scope_records_on_drop_queued", Duration::ZERO);
+        drop(registry);
+
+        // the next sample merges the queued one
+        {
+            crate::profile_scope!("scope_records_on_drop");
+            crate::profile_scope!("inner");
+        }
+        assert!(PENDING.lock().unwrap().is_empty());
+
+        let registry = ReporterRegistry::global();
+        assert!(registry.get("scope_records_on_drop_queued").is_some());
+        assert!(registry
+            .folded_stacks()
+            .get("scope_records_on_drop;inner")
+            .is_some());
+    }
+
+    #[test]
+    fn pending_is_bounded() {
+        let _serial = serial();
+        let registry = ReporterRegistry::global();
+        for _ in 0..MAX_PENDING + 10 {
+            ReporterRegistry::record_global("pending_is_bounded", Duration::ZERO);
+        }
+        assert_eq!(PENDING.lock().unwrap().len(), MAX_PENDING);
+        drop(registry);
+
+        let registry = ReporterRegistry::global();
+        assert!(PENDING.lock().unwrap().is_empty());
+        assert_eq!(
+            registry.get("pending_is_bounded").unwrap().count as usize,
+            MAX_PENDING
+        );
+    }
+
+    #[test]
+    fn nested_self_time() {
+        let _serial = serial();
+        {
+            crate::profile_scope!("nested_self_time");
+            std::thread::sleep(Duration::from_millis(10));
+            {
+                crate::profile_scope!("inner scope");
+                std::thread::sleep(Duration::from_millis(30));
+            }
+        }
+
+        let registry = ReporterRegistry::global();
+        let stacks = registry.folded_stacks();
+        let outer = stacks.get("nested_self_time").unwrap();
+        let inner = stacks.get("nested_self_time;inner_scope").unwrap();
+        assert!(inner >= Duration::from_millis(30));
+        assert!(outer >= Duration::from_millis(10));
+        assert!(outer < inner, "{outer:?} {inner:?}");
+
+        // reporters keep the total time
+        let total = registry.get("nested_self_time").unwrap().elapsed;
+        assert_eq!(total, outer + inner);
+    }
+}
@@ -0,0 +1,498 @@
+use super::{bench::BenchResult, registry::ReporterRegistry, Reporter};
+use std::{error::Error, fmt, fs, io, path::Path, str::FromStr, time::Duration};
+
+//
+
+/// Saved report results for comparing
+/// runs against a known good baseline
+///
+/// The text format has one `section`
+/// line per reported section:
+///
+/// ```
+/// # use main_game_loop::report::snapshot::ReportSnapshot;
+/// let text = "main_game_loop report 1\n\
+///             label\trelease build\n\
+///             section\tFRAME\t4213000\t59.7\n\
+///             section\tUPDATE\t120000\t60\n";
+/// let snapshot: ReportSnapshot = text.parse().unwrap();
+/// assert_eq!(snapshot.to_string(), text);
+/// ```
+///
+/// Fields are separated by a single
+/// tab and durations are in nanoseconds.
+/// Tabs, line breaks and backslashes in
+/// labels and names are escaped as
+/// `\t`, `\n`, `\r` and `\\`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportSnapshot {
+    pub label: String,
+    pub sections: Vec<SectionSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionSnapshot {
+    pub name: String,
+    pub time_per: Duration,
+    pub per_second: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSnapshotError {
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+/// Section by section difference
+/// between two [`ReportSnapshot`]s
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub rows: Vec<ComparisonRow>,
+
+    /// relative change before a section
+    /// counts as regressed or improved
+    pub threshold: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonRow {
+    pub name: String,
+    pub baseline: Option<Duration>,
+    pub current: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Change {
+    Regressed,
+    Improved,
+    Unchanged,
+    Added,
+    Removed,
+}
+
+//
+
+impl ReportSnapshot {
+    const HEADER: &'static str = "main_game_loop report 1";
+
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Snapshot of the last report of every reporter
+    ///
+    /// Reporters that haven't reported
+    /// yet are skipped
+    pub fn from_reporters<'a, I, L>(label: impl Into<String>, reporters: I) -> Self
+    where
+        I: IntoIterator<Item = (L, &'a Reporter)>,
+        L: AsRef<str>,
+    {
+        let sections = reporters
+            .into_iter()
+            .filter_map(|(name, reporter)| {
+                let (time_per, per_second) = reporter.last()?;
+                Some(SectionSnapshot {
+                    name: name.as_ref().to_string(),
+                    time_per,
+                    per_second,
+                })
+            })
+            .collect();
+
+        Self {
+            label: label.into(),
+            sections,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SectionSnapshot> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Compare `self` against `baseline`
+    ///
+    /// `threshold` is the relative change
+    /// in time per sample that is still
+    /// considered noise, `0.1` is 10%
+    pub fn compare(&self, baseline: &Self, threshold: f64) -> Comparison {
+        let current = self.sections.iter().map(|section| ComparisonRow {
+            name: section.name.clone(),
+            baseline: baseline.get(&section.name).map(|b| b.time_per),
+            current: Some(section.time_per),
+        });
+        let removed = baseline
+            .sections
+            .iter()
+            .filter(|section| self.get(&section.name).is_none())
+            .map(|section| ComparisonRow {
+                name: section.name.clone(),
+                baseline: Some(section.time_per),
+                current: None,
+            });
+
+        Comparison {
+            rows: current.chain(removed).collect(),
+            threshold,
+        }
+    }
+}
+
+impl fmt::Display for ReportSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", Self::HEADER)?;
+        writeln!(f, "label\t{}", escape(&self.label))?;
+        for section in self.sections.iter() {
+            writeln!(
+                f,
+                "section\t{}\t{}\t{}",
+                escape(&section.name),
+                section.time_per.as_nanos(),
+                section.per_second
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ReportSnapshot {
+    type Err = ParseSnapshotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let err = |line, reason| ParseSnapshotError { line, reason };
+
+        match lines.next() {
+            Some((_, Self::HEADER)) => {}
+            _ => return Err(err(1, "not a report snapshot")),
+        }
+
+        let mut snapshot = Self::default();
+        for (line, text) in lines.filter(|(_, text)| !text.is_empty()) {
+            let mut fields = text.split('\t');
+            match fields.next() {
+                Some("label") => {
+                    let (Some(label), None) = (fields.next(), fields.next()) else {
+                        return Err(err(line, "expected 1 label field"));
+                    };
+                    snapshot.label = unescape(label).ok_or_else(|| err(line, "invalid escape"))?;
+                }
+                Some("section") => {
+                    let (Some(name), Some(time_per), Some(per_second), None) =
+                        (fields.next(), fields.next(), fields.next(), fields.next())
+                    else {
+                        return Err(err(line, "expected 3 section fields"));
+                    };
+                    let time_per = time_per
+                        .parse()
+                        .map_err(|_| err(line, "invalid time per sample"))?;
+                    let per_second = per_second
+                        .parse()
+                        .map_err(|_| err(line, "invalid samples per second"))?;
+
+                    let name = unescape(name).ok_or_else(|| err(line, "invalid escape"))?;
+
+                    snapshot.sections.push(SectionSnapshot {
+                        name,
+                        time_per: Duration::from_nanos(time_per),
+                        per_second,
+                    });
+                }
+                _ => return Err(err(line, "unknown line")),
+            }
+        }
+
+        Ok(snapshot)
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(s: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next()? {
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            '\\' => '\\',
+            _ => return None,
+        });
+    }
+    Some(unescaped)
+}
+
+impl fmt::Display for ParseSnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Error for ParseSnapshotError {}
+
+impl Comparison {
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+
+    pub fn regressions(&self) -> impl Iterator<Item = &ComparisonRow> {
+        self.rows
+            .iter()
+            .filter(|row| row.change(self.threshold) == Change::Regressed)
+    }
+
+    /// The summary table with ANSI colors,
+    /// regressions in red and improvements in green
+    pub fn to_colored_string(&self) -> String {
+        self.table(true)
+    }
+
+    fn table(&self, colored: bool) -> String {
+        let rows: Vec<_> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let duration = |d: Option<Duration>| {
+                    d.map(|d| format!("{d:.4?}"))
+                        .unwrap_or_else(|| "...".into())
+                };
+                let relative = row
+                    .relative()
+                    .map(|r| format!("{:+.1}%", r * 100.0))
+                    .unwrap_or_default();
+                let change = row.change(self.threshold);
+                (
+                    row.name.as_str(),
+                    duration(row.baseline),
+                    duration(row.current),
+                    relative,
+                    change,
+                )
+            })
+            .collect();
+
+        let name_width = rows.iter().map(|r| r.0.len() + 1).max().unwrap_or(0);
+        let baseline_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(8);
+        let current_width = rows.iter().map(|r| r.2.len()).max().unwrap_or(0).max(7);
+
+        let first = format!(
+            "{:name_width$} {:>baseline_width$} -> {:>current_width$}\n",
+            "", "baseline", "current"
+        );
+
+        Some(first)
+            .into_iter()
+            .chain(rows.iter().map(|(name, baseline, current, relative, change)| {
+                let name = format!("{name}:");
+                let line = format!(
+                    "{name:name_width$} {baseline:>baseline_width$} -> {current:>current_width$} {relative:>7} {change}"
+                );
+                let line = line.trim_end();
+                match (colored, change) {
+                    (true, Change::Regressed) => format!("\x1b[31m{line}\x1b[0m\n"),
+                    (true, Change::Improved) => format!("\x1b[32m{line}\x1b[0m\n"),
+                    (true, Change::Added | Change::Removed) => format!("\x1b[33m{line}\x1b[0m\n"),
+                    _ => format!("{line}\n"),
+                }
+            }))
+            .collect()
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.table(false))
+    }
+}
+
+impl ComparisonRow {
+    /// Relative change in time per sample,
+    /// `0.1` is 10% slower than the baseline
+    pub fn relative(&self) -> Option<f64> {
+        let baseline = self.baseline?.as_secs_f64();
+        let current = self.current?.as_secs_f64();
+        if baseline == 0.0 {
+            return None;
+        }
+        Some(current / baseline - 1.0)
+    }
+
+    pub fn change(&self, threshold: f64) -> Change {
+        match (self.baseline, self.current) {
+            (None, _) => Change::Added,
+            (_, None) => Change::Removed,
+            _ => match self.relative() {
+                Some(r) if r > threshold => Change::Regressed,
+                Some(r) if r < -threshold => Change::Improved,
+                _ => Change::Unchanged,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Change::Regressed => "regressed",
+            Change::Improved => "improved",
+            Change::Unchanged => "",
+            Change::Added => "added",
+            Change::Removed => "removed",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl ReporterRegistry {
+    /// Snapshot of the last report,
+    /// see [`ReportSnapshot::from_reporters`]
+    pub fn snapshot(&self, label: impl Into<String>) -> ReportSnapshot {
+        ReportSnapshot::from_reporters(label, self.iter())
+    }
+}
+
+impl BenchResult {
+    /// Snapshot of the mean of every section
+    pub fn snapshot(&self, label: impl Into<String>) -> ReportSnapshot {
+        let sections = self
+            .iter()
+            .filter_map(|(name, samples)| {
+                let time_per = samples.mean()?;
+                Some(SectionSnapshot {
+                    name: name.to_string(),
+                    time_per,
+                    per_second: 1.0 / time_per.as_secs_f64(),
+                })
+            })
+            .collect();
+
+        ReportSnapshot {
+            label: label.into(),
+            sections,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(name: &str, ms: u64) -> SectionSnapshot {
+        SectionSnapshot {
+            name: name.to_string(),
+            time_per: Duration::from_millis(ms),
+            per_second: 60.0,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let snapshot = ReportSnapshot {
+            label: "release\twith tab".to_string(),
+            sections: vec![
+                section("FRAME", 4),
+                section("UPDATE", 1),
+                section("a\tb\nc\\d\r", 2),
+            ],
+        };
+        let text = snapshot.to_string();
+        assert_eq!(text.lines().count(), 5);
+
+        let parsed: ReportSnapshot = text.parse().unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+
+    #[test]
+    fn parse() {
+        let text = "main_game_loop report 1\nlabel\tci\n\nsection\tFRAME\t4213000\t59.7\n";
+        let snapshot: ReportSnapshot = text.parse().unwrap();
+        assert_eq!(snapshot.label, "ci");
+        assert_eq!(snapshot.sections.len(), 1);
+        assert_eq!(snapshot.sections[0].name, "FRAME");
+        assert_eq!(snapshot.sections[0].time_per, Duration::from_nanos(4213000));
+        assert_eq!(snapshot.sections[0].per_second, 59.7);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |text: &str| text.parse::<ReportSnapshot>().unwrap_err();
+        assert_eq!(err("").line, 1);
+        assert_eq!(err("some other file").line, 1);
+
+        let header = "main_game_loop report 1\n";
+        let e = err(&format!("{header}section\tFRAME\t1"));
+        assert_eq!((e.line, e.reason), (2, "expected 3 section fields"));
+        let e = err(&format!("{header}section\tFRAME\t1\t2\t3"));
+        assert_eq!((e.line, e.reason), (2, "expected 3 section fields"));
+        let e = err(&format!("{header}label\tx\nsection\tFRAME\t-1\t60"));
+        assert_eq!((e.line, e.reason), (3, "invalid time per sample"));
+        let e = err(&format!("{header}section\tFRAME\t1\tfast"));
+        assert_eq!((e.line, e.reason), (2, "invalid samples per second"));
+        let e = err(&format!("{header}frame\t1"));
+        assert_eq!((e.line, e.reason), (2, "unknown line"));
+        let e = err(&format!("{header}section\tFRAME\\x\t1\t60"));
+        assert_eq!((e.line, e.reason), (2, "invalid escape"));
+        let e = err(&format!("{header}label\ta\tb"));
+        assert_eq!((e.line, e.reason), (2, "expected 1 label field"));
+    }
+
+    #[test]
+    fn compare() {
+        let baseline = ReportSnapshot {
+            label: "baseline".to_string(),
+            sections: vec![section("A", 10), section("B", 10), section("C", 10)],
+        };
+        let current = ReportSnapshot {
+            label: "current".to_string(),
+            sections: vec![section("A", 12), section("B", 8), section("D", 1)],
+        };
+
+        let comparison = current.compare(&baseline, 0.1);
+        let changes: Vec<_> = comparison
+            .rows
+            .iter()
+            .map(|row| (row.name.as_str(), row.change(comparison.threshold)))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("A", Change::Regressed),
+                ("B", Change::Improved),
+                ("D", Change::Added),
+                ("C", Change::Removed),
+            ]
+        );
+        assert!(comparison.has_regressions());
+        assert!(!current.compare(&current, 0.0).has_regressions());
+    }
+}
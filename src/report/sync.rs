@@ -0,0 +1,237 @@
+use super::{Reporter, Timer};
+use instant::Instant;
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+//
+
+/// [`Reporter`] that can be shared
+/// between threads with an [`Arc`]
+///
+/// Every thread gets its own counters,
+/// the samples are moved into a regular
+/// [`Reporter`] at report time with
+/// [`Self::merge_into`] or [`Self::report_all`].
+/// Threads that ended are forgotten after
+/// their last samples are merged.
+///
+/// ```no_run
+/// # use main_game_loop::report::{Reporter, sync::SyncReporter};
+/// # use std::sync::Arc;
+/// let streaming = Arc::new(SyncReporter::new());
+///
+/// let worker = streaming.clone();
+/// std::thread::spawn(move || loop {
+///     worker.time(|| { /* load_asset(); */ });
+/// });
+///
+/// let mut streaming_report = Reporter::new();
+/// if streaming_report.should_report() {
+///     log::debug!("\n{}", streaming.report_all("STREAMING", &mut streaming_report));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SyncReporter {
+    id: u64,
+    threads: Mutex<Vec<Arc<ThreadSlot>>>,
+}
+
+#[derive(Debug)]
+struct ThreadSlot {
+    name: String,
+
+    /// count and total elapsed,
+    /// taken together when merging
+    samples: Mutex<(u32, Duration)>,
+}
+
+//
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// slots of this thread, by [`SyncReporter`] id
+    static SLOTS: RefCell<Vec<(u64, Arc<ThreadSlot>)>> = const { RefCell::new(Vec::new()) };
+}
+
+//
+
+impl Default for SyncReporter {
+    fn default() -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            threads: Mutex::default(),
+        }
+    }
+}
+
+impl SyncReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin(&self) -> Timer {
+        Timer {
+            begin: Instant::now(),
+        }
+    }
+
+    pub fn end(&self, timer: Timer) {
+        let elapsed = timer.begin.elapsed();
+        self.with_slot(|slot| {
+            let mut samples = slot.samples.lock().unwrap_or_else(PoisonError::into_inner);
+            samples.0 += 1;
+            samples.1 += elapsed;
+        });
+    }
+
+    pub fn time<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let timer = self.begin();
+        let result = f();
+        self.end(timer);
+        result
+    }
+
+    /// Move every thread's samples into `reporter`
+    ///
+    /// Returns the per thread breakdown of the
+    /// threads that had samples as reporters
+    /// holding only the merged samples, ready
+    /// for [`Reporter::reset`] or [`Reporter::report_all`].
+    pub fn merge_into(&self, reporter: &mut Reporter) -> Vec<(String, Reporter)> {
+        let mut threads = self.threads.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut breakdown = Vec::new();
+
+        threads.retain(|slot| {
+            // only this list is left once the thread ends,
+            // checked first so no sample comes after the take
+            let alive = Arc::strong_count(slot) > 1;
+
+            let (count, elapsed) =
+                std::mem::take(&mut *slot.samples.lock().unwrap_or_else(PoisonError::into_inner));
+            if count > 0 {
+                reporter.merge(count, elapsed);
+
+                let mut thread = Reporter::new_with_interval(reporter.report_interval());
+                thread.merge(count, elapsed);
+                breakdown.push((slot.name.clone(), thread));
+            }
+
+            alive
+        });
+
+        breakdown
+    }
+
+    /// Merges into `reporter` and reports
+    /// it followed by every thread
+    ///
+    /// See [`Reporter::report_all`]
+    pub fn report_all(&self, label: &str, reporter: &mut Reporter) -> String {
+        let mut threads = self.merge_into(reporter);
+        let total = ("total".to_string(), reporter);
+        let threads = threads
+            .iter_mut()
+            .map(|(name, thread)| (format!("  {name}"), thread));
+
+        Reporter::report_all(label, Some(total).into_iter().chain(threads))
+    }
+
+    fn with_slot<F>(&self, f: F)
+    where
+        F: FnOnce(&ThreadSlot),
+    {
+        SLOTS.with(|slots| {
+            let mut slots = slots.borrow_mut();
+            if let Some((_, slot)) = slots.iter().find(|(id, _)| *id == self.id) {
+                return f(slot);
+            }
+
+            // forget the slots of dropped reporters
+            slots.retain(|(_, slot)| Arc::strong_count(slot) > 1);
+
+            let current = thread::current();
+            let slot = Arc::new(ThreadSlot {
+                name: current
+                    .name()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("{:?}", current.id())),
+                samples: Mutex::default(),
+            });
+            f(&slot);
+
+            self.threads
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(slot.clone());
+            slots.push((self.id, slot));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker(reporter: &Arc<SyncReporter>, name: &str, samples: usize) {
+        let reporter = reporter.clone();
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                for _ in 0..samples {
+                    reporter.time(|| {});
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn per_thread() {
+        let reporter = Arc::new(SyncReporter::new());
+        worker(&reporter, "a", 3);
+        worker(&reporter, "b", 2);
+        reporter.time(|| {});
+
+        let mut total = Reporter::new();
+        let mut threads = reporter.merge_into(&mut total);
+        threads.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        assert_eq!(total.count, 6);
+        let counts: Vec<_> = threads
+            .iter()
+            .map(|(name, thread)| (name.as_str(), thread.count))
+            .collect();
+        assert_eq!(counts[..2], [("a", 3), ("b", 2)]);
+        assert_eq!(counts[2].1, 1);
+    }
+
+    #[test]
+    fn ended_threads_are_forgotten() {
+        let reporter = Arc::new(SyncReporter::new());
+        worker(&reporter, "a", 1);
+        reporter.time(|| {});
+        assert_eq!(reporter.threads.lock().unwrap().len(), 2);
+
+        // the last samples of an ended thread are still merged
+        let mut total = Reporter::new();
+        assert_eq!(reporter.merge_into(&mut total).len(), 2);
+        assert_eq!(total.count, 2);
+        assert_eq!(reporter.threads.lock().unwrap().len(), 1);
+
+        // idle threads have no row
+        assert!(reporter.merge_into(&mut total).is_empty());
+        assert!(!reporter.report_all("SYNC", &mut total).contains("ThreadId"));
+    }
+}
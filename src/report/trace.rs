@@ -0,0 +1,114 @@
+use super::{registry::ReporterRegistry, Timer, TRACING_TARGET};
+use instant::Instant;
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::{span, Subscriber};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+//
+
+/// [`Layer`] that times every entered
+/// span into a [`ReporterRegistry`]
+///
+/// Spans are reported by their name.
+/// Spans emitted by [`Reporter::time`](super::Reporter::time)
+/// and [`profile_scope!`](crate::profile_scope)
+/// are skipped, those are already recorded.
+///
+/// ```no_run
+/// # use main_game_loop::report::{registry::ReporterRegistry, trace::ReporterLayer};
+/// use tracing_subscriber::prelude::*;
+///
+/// tracing_subscriber::registry().with(ReporterLayer::new()).init();
+///
+/// tracing::info_span!("UPDATE").in_scope(|| { /* update(); */ });
+/// log::debug!("\n{}", ReporterRegistry::global().report_all("frame"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReporterLayer {
+    registry: Option<Arc<Mutex<ReporterRegistry>>>,
+}
+
+struct Entered(Instant);
+
+//
+
+impl ReporterLayer {
+    /// Record into [`ReporterRegistry::global`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record into `registry` instead of the global one
+    pub fn with_registry(registry: Arc<Mutex<ReporterRegistry>>) -> Self {
+        Self {
+            registry: Some(registry),
+        }
+    }
+
+    fn end(&self, name: &'static str, timer: Timer) {
+        match &self.registry {
+            Some(registry) => registry
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .end(name, timer),
+            None => ReporterRegistry::record_global(name, timer.elapsed()),
+        }
+    }
+}
+
+impl<S> Layer<S> for ReporterLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if span.metadata().target() == TRACING_TARGET {
+            return;
+        }
+
+        span.extensions_mut().replace(Entered(Instant::now()));
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let Some(Entered(begin)) = span.extensions_mut().remove::<Entered>() else {
+            return;
+        };
+
+        self.end(span.name(), Timer { begin });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Reporter;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn spans_by_name() {
+        let registry = Arc::new(Mutex::new(ReporterRegistry::new()));
+        let subscriber =
+            tracing_subscriber::registry().with(ReporterLayer::with_registry(registry.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let update = tracing::info_span!("UPDATE");
+            update.in_scope(|| {});
+            update.in_scope(|| {});
+            tracing::info_span!("DRAW").in_scope(|| {});
+
+            // already recorded by the reporter itself
+            Reporter::new().with_label("FRAME").time(|| {});
+        });
+
+        let registry = registry.lock().unwrap();
+        assert_eq!(registry.get("UPDATE").unwrap().count, 2);
+        assert_eq!(registry.get("DRAW").unwrap().count, 1);
+        let names: Vec<_> = registry.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["UPDATE", "DRAW"]);
+    }
+}
@@ -0,0 +1,408 @@
+use super::{
+    gamepad::{GamepadButton, GamepadButtonInput},
+    input::Input,
+    input_event::InputEvent,
+    position::{KeyPosition, ScanCode},
+};
+use crate::event::Event;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    hash::Hash,
+    ops::{Deref, DerefMut},
+};
+use winit::event::{ElementState, MouseButton, VirtualKeyCode};
+
+//
+
+/// Physical input an action can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(VirtualKeyCode),
+
+    /// layout independent key position,
+    /// WASD stays WASD on AZERTY
+    Position(KeyPosition),
+    ScanCode(ScanCode),
+    Mouse(MouseButton),
+
+    /// the button on any gamepad
+    Gamepad(GamepadButton),
+}
+
+/// User defined actions bound to
+/// keys, mouse buttons and gamepad buttons
+///
+/// An action is pressed while any of its
+/// bindings is pressed. Derefs to
+/// [`Input<A>`] for the usual `pressed`,
+/// `just_pressed` and `just_released` queries.
+///
+/// ```no_run
+/// # use main_game_loop::prelude::*;
+/// # use winit::event::VirtualKeyCode;
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// enum Action {
+///     Jump,
+///     Fire,
+/// }
+///
+/// let mut actions = ActionState::new()
+///     .with_auto_clear()
+///     .with_binding(Action::Jump, Binding::Key(VirtualKeyCode::Space))
+///     .with_binding(Action::Jump, Binding::Position(KeyPosition::KeyW))
+///     .with_binding(Action::Jump, Binding::Gamepad(GamepadButton::South))
+///     .with_binding(Action::Fire, Binding::Mouse(MouseButton::Left));
+///
+/// // actions.event(&event);
+/// if actions.just_pressed(Action::Jump) {
+///     // jump();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ActionState<A> {
+    inner: Input<A>,
+    bindings: FxHashMap<A, Vec<Binding>>,
+    held: FxHashSet<Source>,
+    auto_clear: bool,
+    release_on_unfocus: bool,
+}
+
+/// A single held input, gamepads separated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Source {
+    Key(VirtualKeyCode),
+    ScanCode(ScanCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonInput),
+}
+
+//
+
+impl Binding {
+    fn matches(self, source: Source) -> bool {
+        match (self, source) {
+            (Binding::Key(a), Source::Key(b)) => a == b,
+            (Binding::Position(a), Source::ScanCode(b)) => a.scancode() == Some(b),
+            (Binding::ScanCode(a), Source::ScanCode(b)) => a == b,
+            (Binding::Mouse(a), Source::Mouse(b)) => a == b,
+            (Binding::Gamepad(a), Source::Gamepad(b)) => a == b.button,
+            _ => false,
+        }
+    }
+}
+
+impl<A> Default for ActionState<A>
+where
+    A: Copy + Eq + Hash,
+{
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+            bindings: Default::default(),
+            held: Default::default(),
+            auto_clear: false,
+            release_on_unfocus: false,
+        }
+    }
+}
+
+impl<A> ActionState<A>
+where
+    A: Copy + Eq + Hash,
+{
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_auto_clear(mut self) -> Self {
+        self.set_auto_clear(true);
+        self
+    }
+
+    #[inline]
+    pub fn set_auto_clear(&mut self, auto_clear: bool) {
+        self.auto_clear = auto_clear;
+    }
+
+    /// Release everything when the
+    /// window loses focus, see
+    /// [`Input::release_all`]
+    #[inline]
+    pub fn with_release_on_unfocus(mut self) -> Self {
+        self.set_release_on_unfocus(true);
+        self
+    }
+
+    #[inline]
+    pub fn set_release_on_unfocus(&mut self, release_on_unfocus: bool) {
+        self.release_on_unfocus = release_on_unfocus;
+    }
+
+    #[inline]
+    pub fn with_binding(mut self, action: A, binding: Binding) -> Self {
+        self.bind(action, binding);
+        self
+    }
+
+    // bindings
+
+    pub fn bind(&mut self, action: A, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self.refresh(action);
+    }
+
+    pub fn unbind(&mut self, action: A, binding: Binding) {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            bindings.retain(|b| *b != binding);
+        }
+        self.refresh(action);
+    }
+
+    pub fn clear_bindings(&mut self, action: A) {
+        self.bindings.remove(&action);
+        self.refresh(action);
+    }
+
+    pub fn bindings(&self, action: A) -> impl Iterator<Item = Binding> + '_ {
+        self.bindings.get(&action).into_iter().flatten().copied()
+    }
+
+    /// Every action `binding` is bound to
+    pub fn actions_bound_to(&self, binding: Binding) -> impl Iterator<Item = A> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| *action)
+    }
+
+    //
+
+    pub fn event(&mut self, event: &Event) {
+        if let Some(event) = InputEvent::from_event(event) {
+            self.input_event(&event);
+        }
+    }
+
+    pub fn input_event(&mut self, event: &InputEvent) {
+        match *event {
+            // kb events
+            InputEvent::Key {
+                state,
+                scancode,
+                virtual_keycode,
+            } => {
+                self.source(state, Source::ScanCode(scancode));
+                if let Some(virtual_keycode) = virtual_keycode {
+                    self.source(state, Source::Key(virtual_keycode));
+                }
+            }
+
+            // mouse events
+            InputEvent::MouseButton { state, button } => self.source(state, Source::Mouse(button)),
+
+            // gamepad events
+            InputEvent::GamepadButton {
+                gamepad,
+                state,
+                button,
+            } => {
+                let input = GamepadButtonInput { gamepad, button };
+                self.source(state, Source::Gamepad(input));
+            }
+
+            InputEvent::Focused(false) if self.release_on_unfocus => {
+                self.held.clear();
+                self.inner.release_all();
+            }
+
+            // auto clear event
+            InputEvent::RedrawEventsCleared if self.auto_clear => self.inner.clear(),
+
+            _ => {}
+        }
+    }
+
+    fn source(&mut self, state: ElementState, source: Source) {
+        match state {
+            ElementState::Pressed => self.held.insert(source),
+            ElementState::Released => self.held.remove(&source),
+        };
+
+        let actions: Vec<A> = self
+            .bindings
+            .iter()
+            .filter(|(_, bindings)| bindings.iter().any(|b| b.matches(source)))
+            .map(|(action, _)| *action)
+            .collect();
+        for action in actions {
+            self.refresh(action);
+        }
+    }
+
+    /// Sync the action with its bindings
+    fn refresh(&mut self, action: A) {
+        let held = self.bindings.get(&action).is_some_and(|bindings| {
+            bindings
+                .iter()
+                .any(|b| self.held.iter().any(|source| b.matches(*source)))
+        });
+
+        match (held, self.inner.pressed(action)) {
+            (true, false) => self.inner.event(ElementState::Pressed, action),
+            (false, true) => self.inner.event(ElementState::Released, action),
+            _ => {}
+        }
+    }
+}
+
+impl<A> Deref for ActionState<A> {
+    type Target = Input<A>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<A> DerefMut for ActionState<A> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::ElementState::{Pressed, Released};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Action {
+        Jump,
+        Fire,
+    }
+
+    fn key(state: ElementState, scancode: ScanCode, key: Option<VirtualKeyCode>) -> InputEvent {
+        InputEvent::Key {
+            state,
+            scancode,
+            virtual_keycode: key,
+        }
+    }
+
+    fn space(state: ElementState) -> InputEvent {
+        key(state, 0, Some(VirtualKeyCode::Space))
+    }
+
+    fn click(state: ElementState) -> InputEvent {
+        InputEvent::MouseButton {
+            state,
+            button: MouseButton::Left,
+        }
+    }
+
+    #[test]
+    fn any_binding_holds() {
+        let mut actions = ActionState::new()
+            .with_binding(Action::Jump, Binding::Key(VirtualKeyCode::Space))
+            .with_binding(Action::Jump, Binding::Mouse(MouseButton::Left));
+
+        actions.input_event(&space(Pressed));
+        actions.input_event(&click(Pressed));
+        actions.input_event(&space(Released));
+        assert!(actions.pressed(Action::Jump));
+        assert!(!actions.just_released(Action::Jump));
+
+        actions.input_event(&click(Released));
+        assert!(!actions.pressed(Action::Jump));
+        assert!(actions.just_released(Action::Jump));
+        assert!(!actions.pressed(Action::Fire));
+    }
+
+    #[test]
+    fn unbind_while_held() {
+        let mut actions =
+            ActionState::new().with_binding(Action::Fire, Binding::Mouse(MouseButton::Left));
+        actions.input_event(&click(Pressed));
+        assert!(actions.pressed(Action::Fire));
+
+        actions.unbind(Action::Fire, Binding::Mouse(MouseButton::Left));
+        assert!(!actions.pressed(Action::Fire));
+        assert!(actions.just_released(Action::Fire));
+
+        // rebinding picks up the held button
+        actions.clear();
+        actions.bind(Action::Fire, Binding::Mouse(MouseButton::Left));
+        assert!(actions.just_pressed(Action::Fire));
+        assert_eq!(
+            actions
+                .actions_bound_to(Binding::Mouse(MouseButton::Left))
+                .collect::<Vec<_>>(),
+            [Action::Fire]
+        );
+    }
+
+    #[test]
+    fn position_through_scancode() {
+        let position = KeyPosition::KeyW;
+        let mut actions =
+            ActionState::new().with_binding(Action::Jump, Binding::Position(position));
+
+        // `Z` on AZERTY
+        let scancode = position.scancode().unwrap();
+        actions.input_event(&key(Pressed, scancode, Some(VirtualKeyCode::Z)));
+        assert!(actions.just_pressed(Action::Jump));
+
+        actions.input_event(&key(Released, scancode, None));
+        assert!(!actions.pressed(Action::Jump));
+    }
+
+    #[test]
+    #[cfg(feature = "record")]
+    fn same_button_on_two_gamepads() {
+        use crate::state::gamepad::Gamepad;
+        use bincode::Options;
+
+        // gilrs has no public constructor for ids
+        let gamepad =
+            |id: u8| -> Gamepad { bincode::DefaultOptions::new().deserialize(&[id]).unwrap() };
+        let button = |gamepad, state| InputEvent::GamepadButton {
+            gamepad,
+            state,
+            button: GamepadButton::South,
+        };
+
+        let mut actions =
+            ActionState::new().with_binding(Action::Jump, Binding::Gamepad(GamepadButton::South));
+        actions.input_event(&button(gamepad(0), Pressed));
+        actions.input_event(&button(gamepad(1), Pressed));
+        actions.input_event(&button(gamepad(0), Released));
+        assert!(actions.pressed(Action::Jump));
+
+        actions.input_event(&button(gamepad(1), Released));
+        assert!(!actions.pressed(Action::Jump));
+    }
+
+    #[test]
+    fn release_on_unfocus() {
+        let mut actions = ActionState::new()
+            .with_release_on_unfocus()
+            .with_binding(Action::Jump, Binding::Key(VirtualKeyCode::Space));
+
+        actions.input_event(&space(Pressed));
+        actions.clear();
+        actions.input_event(&InputEvent::Focused(false));
+        assert!(!actions.pressed(Action::Jump));
+        assert!(actions.just_released(Action::Jump));
+
+        // the late release changes nothing, the next press counts
+        actions.clear();
+        actions.input_event(&space(Released));
+        assert!(!actions.just_released(Action::Jump));
+        actions.input_event(&space(Pressed));
+        assert!(actions.just_pressed(Action::Jump));
+    }
+}
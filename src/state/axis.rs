@@ -0,0 +1,479 @@
+use super::{
+    gamepad::{GamepadAxis, GamepadAxisInput, GamepadButton, GamepadButtonInput, GamepadState},
+    keyboard::KeyboardState,
+};
+use glam::Vec2;
+use winit::event::VirtualKeyCode;
+
+//
+
+/// Source of a [`VirtualAxis`] value
+///
+/// Gamepad bindings read every connected gamepad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisBinding {
+    Keys {
+        negative: VirtualKeyCode,
+        positive: VirtualKeyCode,
+    },
+    Buttons {
+        negative: GamepadButton,
+        positive: GamepadButton,
+    },
+    Stick(GamepadAxis),
+}
+
+/// Source of a [`VirtualAxis2`] value
+///
+/// Positive y is up, like gamepad sticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis2Binding {
+    Keys {
+        up: VirtualKeyCode,
+        down: VirtualKeyCode,
+        left: VirtualKeyCode,
+        right: VirtualKeyCode,
+    },
+    Buttons {
+        up: GamepadButton,
+        down: GamepadButton,
+        left: GamepadButton,
+        right: GamepadButton,
+    },
+    Stick {
+        x: GamepadAxis,
+        y: GamepadAxis,
+    },
+}
+
+/// How multiple active bindings are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AxisMode {
+    /// the binding with the largest magnitude
+    #[default]
+    Strongest,
+
+    /// the binding that became active last
+    MostRecent,
+}
+
+/// 1D axis in `-1.0..=1.0` from key pairs,
+/// gamepad button pairs and sticks
+///
+/// Call [`VirtualAxis::update`] once per frame.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualAxis {
+    bindings: Vec<AxisBinding>,
+    select: Select,
+    value: f32,
+}
+
+/// 2D axis with a length of at most `1.0`
+/// from key sets, gamepad buttons and sticks
+///
+/// Digital diagonals are normalized so
+/// that moving diagonally isn't faster.
+///
+/// ```no_run
+/// # use main_game_loop::prelude::*;
+/// let mut movement = VirtualAxis2::new()
+///     .with_binding(Axis2Binding::WASD)
+///     .with_binding(Axis2Binding::ARROWS)
+///     .with_binding(Axis2Binding::D_PAD)
+///     .with_binding(Axis2Binding::LEFT_STICK);
+///
+/// let keyboard = KeyboardState::new();
+/// let gamepad = GamepadState::new();
+/// let dir: glam::Vec2 = movement.update(&keyboard, &gamepad);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VirtualAxis2 {
+    bindings: Vec<Axis2Binding>,
+    select: Select,
+    value: Vec2,
+}
+
+/// Picks one of the active bindings
+#[derive(Debug, Clone, Default)]
+struct Select {
+    mode: AxisMode,
+
+    /// activation tick of each binding,
+    /// `None` if the binding is inactive
+    activated: Vec<Option<u64>>,
+    tick: u64,
+}
+
+//
+
+impl AxisBinding {
+    pub const AD: Self = Self::Keys {
+        negative: VirtualKeyCode::A,
+        positive: VirtualKeyCode::D,
+    };
+    pub const LEFT_RIGHT: Self = Self::Keys {
+        negative: VirtualKeyCode::Left,
+        positive: VirtualKeyCode::Right,
+    };
+    pub const D_PAD_X: Self = Self::Buttons {
+        negative: GamepadButton::DPadLeft,
+        positive: GamepadButton::DPadRight,
+    };
+    pub const LEFT_STICK_X: Self = Self::Stick(GamepadAxis::LeftStickX);
+
+    pub fn value(&self, keyboard: &KeyboardState, gamepad: &GamepadState) -> f32 {
+        match *self {
+            AxisBinding::Keys { negative, positive } => key_axis(keyboard, negative, positive),
+            AxisBinding::Buttons { negative, positive } => button_axis(gamepad, negative, positive),
+            AxisBinding::Stick(axis) => stick(gamepad, axis),
+        }
+    }
+}
+
+impl Axis2Binding {
+    pub const WASD: Self = Self::Keys {
+        up: VirtualKeyCode::W,
+        down: VirtualKeyCode::S,
+        left: VirtualKeyCode::A,
+        right: VirtualKeyCode::D,
+    };
+    pub const ARROWS: Self = Self::Keys {
+        up: VirtualKeyCode::Up,
+        down: VirtualKeyCode::Down,
+        left: VirtualKeyCode::Left,
+        right: VirtualKeyCode::Right,
+    };
+    pub const D_PAD: Self = Self::Buttons {
+        up: GamepadButton::DPadUp,
+        down: GamepadButton::DPadDown,
+        left: GamepadButton::DPadLeft,
+        right: GamepadButton::DPadRight,
+    };
+    pub const LEFT_STICK: Self = Self::Stick {
+        x: GamepadAxis::LeftStickX,
+        y: GamepadAxis::LeftStickY,
+    };
+    pub const RIGHT_STICK: Self = Self::Stick {
+        x: GamepadAxis::RightStickX,
+        y: GamepadAxis::RightStickY,
+    };
+
+    /// Value of this binding alone, normalized
+    pub fn value(&self, keyboard: &KeyboardState, gamepad: &GamepadState) -> Vec2 {
+        let value = match *self {
+            Axis2Binding::Keys {
+                up,
+                down,
+                left,
+                right,
+            } => Vec2::new(
+                key_axis(keyboard, left, right),
+                key_axis(keyboard, down, up),
+            ),
+            Axis2Binding::Buttons {
+                up,
+                down,
+                left,
+                right,
+            } => Vec2::new(
+                button_axis(gamepad, left, right),
+                button_axis(gamepad, down, up),
+            ),
+            Axis2Binding::Stick { x, y } => strongest_stick(gamepad, x, y),
+        };
+        value.clamp_length_max(1.0)
+    }
+}
+
+impl VirtualAxis {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_binding(mut self, binding: AxisBinding) -> Self {
+        self.bind(binding);
+        self
+    }
+
+    #[inline]
+    pub fn with_mode(mut self, mode: AxisMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    pub fn bind(&mut self, binding: AxisBinding) {
+        if !self.bindings.contains(&binding) {
+            self.bindings.push(binding);
+        }
+    }
+
+    pub fn unbind(&mut self, binding: AxisBinding) {
+        if let Some(i) = self.bindings.iter().position(|b| *b == binding) {
+            self.bindings.remove(i);
+            self.select.remove(i);
+        }
+    }
+
+    pub fn bindings(&self) -> &[AxisBinding] {
+        &self.bindings
+    }
+
+    #[inline]
+    pub fn set_mode(&mut self, mode: AxisMode) {
+        self.select.mode = mode;
+    }
+
+    #[inline]
+    pub fn mode(&self) -> AxisMode {
+        self.select.mode
+    }
+
+    /// Read every binding and pick the new value
+    pub fn update(&mut self, keyboard: &KeyboardState, gamepad: &GamepadState) -> f32 {
+        let values: Vec<f32> = self
+            .bindings
+            .iter()
+            .map(|binding| binding.value(keyboard, gamepad))
+            .collect();
+        self.value = self
+            .select
+            .pick(values.iter().map(|v| v.abs()))
+            .map_or(0.0, |i| values[i]);
+        self.value
+    }
+
+    /// Value from the last [`VirtualAxis::update`]
+    #[inline]
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+}
+
+impl VirtualAxis2 {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// WASD, arrow keys, D-pad and the left stick
+    pub fn movement() -> Self {
+        Self::new()
+            .with_binding(Axis2Binding::WASD)
+            .with_binding(Axis2Binding::ARROWS)
+            .with_binding(Axis2Binding::D_PAD)
+            .with_binding(Axis2Binding::LEFT_STICK)
+    }
+
+    #[inline]
+    pub fn with_binding(mut self, binding: Axis2Binding) -> Self {
+        self.bind(binding);
+        self
+    }
+
+    #[inline]
+    pub fn with_mode(mut self, mode: AxisMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    pub fn bind(&mut self, binding: Axis2Binding) {
+        if !self.bindings.contains(&binding) {
+            self.bindings.push(binding);
+        }
+    }
+
+    pub fn unbind(&mut self, binding: Axis2Binding) {
+        if let Some(i) = self.bindings.iter().position(|b| *b == binding) {
+            self.bindings.remove(i);
+            self.select.remove(i);
+        }
+    }
+
+    pub fn bindings(&self) -> &[Axis2Binding] {
+        &self.bindings
+    }
+
+    #[inline]
+    pub fn set_mode(&mut self, mode: AxisMode) {
+        self.select.mode = mode;
+    }
+
+    #[inline]
+    pub fn mode(&self) -> AxisMode {
+        self.select.mode
+    }
+
+    /// Read every binding and pick the new value
+    pub fn update(&mut self, keyboard: &KeyboardState, gamepad: &GamepadState) -> Vec2 {
+        let values: Vec<Vec2> = self
+            .bindings
+            .iter()
+            .map(|binding| binding.value(keyboard, gamepad))
+            .collect();
+        self.value = self
+            .select
+            .pick(values.iter().map(|v| v.length()))
+            .map_or(Vec2::ZERO, |i| values[i]);
+        self.value
+    }
+
+    /// Value from the last [`VirtualAxis2::update`]
+    #[inline]
+    pub fn value(&self) -> Vec2 {
+        self.value
+    }
+}
+
+impl Select {
+    /// Index of the chosen active binding
+    fn pick(&mut self, magnitudes: impl Iterator<Item = f32>) -> Option<usize> {
+        let magnitudes: Vec<f32> = magnitudes.collect();
+        self.activated.resize(magnitudes.len(), None);
+        self.tick += 1;
+
+        for (activated, magnitude) in self.activated.iter_mut().zip(magnitudes.iter()) {
+            match (*activated, *magnitude > 0.0) {
+                (None, true) => *activated = Some(self.tick),
+                (Some(_), false) => *activated = None,
+                _ => {}
+            }
+        }
+
+        match self.mode {
+            AxisMode::Strongest => magnitudes
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| **m > 0.0)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(i, _)| i),
+            AxisMode::MostRecent => self
+                .activated
+                .iter()
+                .enumerate()
+                .filter_map(|(i, tick)| Some((i, (*tick)?)))
+                .max_by_key(|(_, tick)| *tick)
+                .map(|(i, _)| i),
+        }
+    }
+
+    fn remove(&mut self, i: usize) {
+        if i < self.activated.len() {
+            self.activated.remove(i);
+        }
+    }
+}
+
+fn key_axis(keyboard: &KeyboardState, negative: VirtualKeyCode, positive: VirtualKeyCode) -> f32 {
+    keyboard.pressed(positive) as u8 as f32 - keyboard.pressed(negative) as u8 as f32
+}
+
+fn button_axis(gamepad: &GamepadState, negative: GamepadButton, positive: GamepadButton) -> f32 {
+    let pressed = |button| {
+        gamepad.gamepads().any(|gamepad_id| {
+            gamepad.pressed(GamepadButtonInput {
+                gamepad: gamepad_id,
+                button,
+            })
+        })
+    };
+    pressed(positive) as u8 as f32 - pressed(negative) as u8 as f32
+}
+
+fn stick(gamepad: &GamepadState, axis: GamepadAxis) -> f32 {
+    gamepad
+        .gamepads()
+        .map(|gamepad_id| {
+            gamepad.axis_value(GamepadAxisInput {
+                gamepad: gamepad_id,
+                axis,
+            })
+        })
+        .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap_or(0.0)
+}
+
+fn strongest_stick(gamepad: &GamepadState, x: GamepadAxis, y: GamepadAxis) -> Vec2 {
+    gamepad
+        .gamepads()
+        .map(|gamepad_id| {
+            Vec2::new(
+                gamepad.axis_value(GamepadAxisInput {
+                    gamepad: gamepad_id,
+                    axis: x,
+                }),
+                gamepad.axis_value(GamepadAxisInput {
+                    gamepad: gamepad_id,
+                    axis: y,
+                }),
+            )
+        })
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or(Vec2::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::input_event::InputEvent;
+    use winit::event::ElementState;
+
+    fn select(mode: AxisMode) -> Select {
+        Select {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    fn key(keyboard: &mut KeyboardState, state: ElementState, key: VirtualKeyCode) {
+        keyboard.input_event(&InputEvent::Key {
+            state,
+            scancode: 0,
+            virtual_keycode: Some(key),
+        });
+    }
+
+    #[test]
+    fn pick_strongest() {
+        let mut select = select(AxisMode::Strongest);
+        assert_eq!(select.pick([0.0, 0.0].into_iter()), None);
+        assert_eq!(select.pick([0.3, 0.8].into_iter()), Some(1));
+        assert_eq!(select.pick([1.0, 0.8].into_iter()), Some(0));
+    }
+
+    #[test]
+    fn pick_most_recent() {
+        let mut select = select(AxisMode::MostRecent);
+        assert_eq!(select.pick([1.0, 0.0].into_iter()), Some(0));
+        assert_eq!(select.pick([1.0, 0.2].into_iter()), Some(1));
+
+        // the older binding takes over once the newer one is released
+        assert_eq!(select.pick([1.0, 0.0].into_iter()), Some(0));
+
+        // and a released then pressed again binding is the newest
+        assert_eq!(select.pick([0.0, 0.5].into_iter()), Some(1));
+        assert_eq!(select.pick([1.0, 0.5].into_iter()), Some(0));
+
+        select.remove(0);
+        assert_eq!(select.pick([0.5].into_iter()), Some(0));
+    }
+
+    #[test]
+    fn keys_axis2() {
+        let mut keyboard = KeyboardState::new();
+        let mut axis = VirtualAxis2::movement();
+
+        key(&mut keyboard, ElementState::Pressed, VirtualKeyCode::W);
+        assert_eq!(axis.update(&keyboard, &GamepadState::new()), Vec2::Y);
+
+        key(&mut keyboard, ElementState::Pressed, VirtualKeyCode::D);
+        let value = axis.update(&keyboard, &GamepadState::new());
+        assert!((value.length() - 1.0).abs() < 1e-6);
+        assert!(value.x > 0.0 && value.y > 0.0);
+
+        key(&mut keyboard, ElementState::Released, VirtualKeyCode::W);
+        key(&mut keyboard, ElementState::Released, VirtualKeyCode::D);
+        assert_eq!(axis.update(&keyboard, &GamepadState::new()), Vec2::ZERO);
+    }
+}
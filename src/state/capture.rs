@@ -0,0 +1,384 @@
+use super::{
+    action::{ActionState, Binding},
+    axis::{AxisBinding, VirtualAxis},
+    gamepad::{GamepadAxis, GamepadAxisInput, GamepadButtonInput, GamepadState},
+    input::Input,
+    keyboard::KeyboardState,
+    mouse::{MouseButton, MouseState},
+    position::{KeyPosition, ScanCode},
+};
+use rustc_hash::FxHashSet;
+use std::hash::Hash;
+use winit::event::VirtualKeyCode;
+
+//
+
+/// "Press any key" capture for rebinding
+///
+/// Inputs held or just pressed when the
+/// capture starts are ignored until released,
+/// like the click on a "Rebind" button. Poll
+/// once per frame, before the states are cleared.
+///
+/// ```no_run
+/// # use main_game_loop::prelude::*;
+/// # #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # enum Action { Jump }
+/// # let mut actions = ActionState::<Action>::new();
+/// let (keyboard, gamepad, mouse) = (KeyboardState::new(), GamepadState::new(), MouseState::new());
+/// let mut capture = InputCapture::new().with_positions();
+/// capture.start(&keyboard, &gamepad, &mouse);
+///
+/// // every frame
+/// match capture.poll(&keyboard, &gamepad, &mouse) {
+///     Some(Captured::Binding(binding)) => {
+///         let conflicts: Vec<Action> = actions.actions_bound_to(binding).collect();
+///         // ask about the conflicts, then
+///         actions.bind(Action::Jump, binding);
+///     }
+///     Some(Captured::Axis(_)) => { /* only for axis bindings */ }
+///     Some(Captured::Cancelled) => {}
+///     None => {}
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InputCapture {
+    active: bool,
+    cancel: Option<VirtualKeyCode>,
+    axis_threshold: f32,
+    positions: bool,
+    mouse: bool,
+
+    // held or just pressed when the capture started
+    held_keys: FxHashSet<VirtualKeyCode>,
+    held_scancodes: FxHashSet<ScanCode>,
+    held_mouse: FxHashSet<MouseButton>,
+    held_buttons: FxHashSet<GamepadButtonInput>,
+    held_axes: FxHashSet<GamepadAxisInput>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Captured {
+    Binding(Binding),
+
+    /// a stick or trigger moved past the threshold
+    Axis(AxisBinding),
+
+    /// the cancel key was pressed
+    Cancelled,
+}
+
+//
+
+const AXES: [GamepadAxis; 8] = [
+    GamepadAxis::LeftStickX,
+    GamepadAxis::LeftStickY,
+    GamepadAxis::LeftZ,
+    GamepadAxis::RightStickX,
+    GamepadAxis::RightStickY,
+    GamepadAxis::RightZ,
+    GamepadAxis::DPadX,
+    GamepadAxis::DPadY,
+];
+
+impl Default for InputCapture {
+    fn default() -> Self {
+        Self {
+            active: false,
+            cancel: Some(VirtualKeyCode::Escape),
+            axis_threshold: 0.5,
+            positions: false,
+            mouse: true,
+            held_keys: Default::default(),
+            held_scancodes: Default::default(),
+            held_mouse: Default::default(),
+            held_buttons: Default::default(),
+            held_axes: Default::default(),
+        }
+    }
+}
+
+impl InputCapture {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Key that cancels the capture,
+    /// `Escape` by default
+    #[inline]
+    pub fn with_cancel_key(mut self, cancel: Option<VirtualKeyCode>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// How far a stick or trigger has to
+    /// move to be captured, `0.5` by default
+    #[inline]
+    pub fn with_axis_threshold(mut self, axis_threshold: f32) -> Self {
+        self.axis_threshold = axis_threshold;
+        self
+    }
+
+    /// Capture keys as [`Binding::Position`]
+    /// when the position is known
+    #[inline]
+    pub fn with_positions(mut self) -> Self {
+        self.positions = true;
+        self
+    }
+
+    /// Capture mouse buttons, on by default
+    #[inline]
+    pub fn with_mouse(mut self, mouse: bool) -> Self {
+        self.mouse = mouse;
+        self
+    }
+
+    /// Start capturing, ignoring everything held
+    /// or just pressed right now until it is released
+    pub fn start(&mut self, keyboard: &KeyboardState, gamepad: &GamepadState, mouse: &MouseState) {
+        self.active = true;
+        self.held_keys = held(keyboard);
+        self.held_scancodes = held(keyboard.scancodes());
+        self.held_mouse = held(mouse);
+        self.held_buttons = held(gamepad);
+        self.held_axes = self.axes(gamepad).map(|(input, _)| input).collect();
+    }
+
+    #[inline]
+    pub fn stop(&mut self) {
+        self.active = false;
+    }
+
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// The first new input since the capture
+    /// started, stops the capture when found
+    pub fn poll(
+        &mut self,
+        keyboard: &KeyboardState,
+        gamepad: &GamepadState,
+        mouse: &MouseState,
+    ) -> Option<Captured> {
+        if !self.active {
+            return None;
+        }
+
+        // forget the held inputs once released,
+        // presses from the start frame are kept
+        // until the states are cleared
+        retain_held(&mut self.held_keys, keyboard);
+        retain_held(&mut self.held_scancodes, keyboard.scancodes());
+        retain_held(&mut self.held_mouse, mouse);
+        retain_held(&mut self.held_buttons, gamepad);
+        let moved: FxHashSet<_> = self.axes(gamepad).map(|(input, _)| input).collect();
+        self.held_axes.retain(|input| moved.contains(input));
+
+        let captured = self.find(keyboard, gamepad, mouse, &moved);
+        if captured.is_some() {
+            self.active = false;
+        }
+        captured
+    }
+
+    fn find(
+        &self,
+        keyboard: &KeyboardState,
+        gamepad: &GamepadState,
+        mouse: &MouseState,
+        moved: &FxHashSet<GamepadAxisInput>,
+    ) -> Option<Captured> {
+        if let Some(cancel) = self.cancel {
+            if keyboard.just_pressed(cancel) && !self.held_keys.contains(&cancel) {
+                return Some(Captured::Cancelled);
+            }
+        }
+
+        if self.positions {
+            let position = new_presses(
+                keyboard.scancodes().iter_just_pressed(),
+                &self.held_scancodes,
+            )
+            .find_map(KeyPosition::from_scancode);
+            if let Some(position) = position {
+                return Some(Captured::Binding(Binding::Position(position)));
+            }
+        }
+
+        if let Some(key) = new_presses(keyboard.iter_just_pressed(), &self.held_keys).next() {
+            return Some(Captured::Binding(Binding::Key(key)));
+        }
+
+        if self.mouse {
+            if let Some(button) = new_presses(mouse.iter_just_pressed(), &self.held_mouse).next() {
+                return Some(Captured::Binding(Binding::Mouse(button)));
+            }
+        }
+
+        if let Some(input) = new_presses(gamepad.iter_just_pressed(), &self.held_buttons).next() {
+            return Some(Captured::Binding(Binding::Gamepad(input.button)));
+        }
+
+        moved
+            .iter()
+            .find(|input| !self.held_axes.contains(input))
+            .map(|input| Captured::Axis(AxisBinding::Stick(input.axis)))
+    }
+
+    /// Axes past the threshold
+    fn axes<'a>(
+        &'a self,
+        gamepad: &'a GamepadState,
+    ) -> impl Iterator<Item = (GamepadAxisInput, f32)> + 'a {
+        gamepad
+            .gamepads()
+            .flat_map(|gamepad| AXES.map(|axis| GamepadAxisInput { gamepad, axis }))
+            .map(|input| (input, gamepad.axis_value(input)))
+            .filter(|(_, value)| value.abs() >= self.axis_threshold)
+    }
+}
+
+impl Captured {
+    /// Actions already bound to the captured input
+    pub fn conflicts<A>(&self, actions: &ActionState<A>) -> Vec<A>
+    where
+        A: Copy + Eq + Hash,
+    {
+        match self {
+            Captured::Binding(binding) => actions.actions_bound_to(*binding).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The captured axis is already bound to `axis`
+    pub fn conflicts_with_axis(&self, axis: &VirtualAxis) -> bool {
+        match self {
+            Captured::Axis(binding) => axis.bindings().contains(binding),
+            _ => false,
+        }
+    }
+}
+
+fn held<T>(input: &Input<T>) -> FxHashSet<T>
+where
+    T: Copy + Eq + Hash,
+{
+    input
+        .iter_pressed()
+        .chain(input.iter_just_pressed())
+        .copied()
+        .collect()
+}
+
+fn retain_held<T>(held: &mut FxHashSet<T>, input: &Input<T>)
+where
+    T: Copy + Eq + Hash,
+{
+    held.retain(|code| input.pressed(*code) || input.just_pressed(*code));
+}
+
+fn new_presses<'a, T>(
+    just_pressed: impl Iterator<Item = &'a T> + 'a,
+    held: &'a FxHashSet<T>,
+) -> impl Iterator<Item = T> + 'a
+where
+    T: Copy + Eq + Hash + 'a,
+{
+    just_pressed
+        .copied()
+        .filter(move |code| !held.contains(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::input_event::InputEvent;
+    use winit::event::ElementState;
+
+    fn click(mouse: &mut MouseState, state: ElementState) {
+        mouse.input_event(&InputEvent::MouseButton {
+            state,
+            button: MouseButton::Left,
+        });
+    }
+
+    fn key(keyboard: &mut KeyboardState, state: ElementState, key: VirtualKeyCode) {
+        keyboard.input_event(&InputEvent::Key {
+            state,
+            scancode: 0,
+            virtual_keycode: Some(key),
+        });
+    }
+
+    #[test]
+    fn start_frame_click_is_ignored() {
+        let (keyboard, gamepad, mut mouse) =
+            (KeyboardState::new(), GamepadState::new(), MouseState::new());
+        let mut capture = InputCapture::new();
+
+        // the click on "Rebind" ends in the start frame
+        click(&mut mouse, ElementState::Pressed);
+        click(&mut mouse, ElementState::Released);
+        capture.start(&keyboard, &gamepad, &mouse);
+        assert_eq!(capture.poll(&keyboard, &gamepad, &mouse), None);
+        mouse.clear();
+
+        assert_eq!(capture.poll(&keyboard, &gamepad, &mouse), None);
+        click(&mut mouse, ElementState::Pressed);
+        assert_eq!(
+            capture.poll(&keyboard, &gamepad, &mouse),
+            Some(Captured::Binding(Binding::Mouse(MouseButton::Left)))
+        );
+        assert!(!capture.is_active());
+    }
+
+    #[test]
+    fn held_key_is_ignored_until_released() {
+        let (mut keyboard, gamepad, mouse) =
+            (KeyboardState::new(), GamepadState::new(), MouseState::new());
+        let mut capture = InputCapture::new();
+
+        key(&mut keyboard, ElementState::Pressed, VirtualKeyCode::Return);
+        capture.start(&keyboard, &gamepad, &mouse);
+        assert_eq!(capture.poll(&keyboard, &gamepad, &mouse), None);
+        keyboard.clear();
+
+        // key repeat
+        key(&mut keyboard, ElementState::Pressed, VirtualKeyCode::Return);
+        assert_eq!(capture.poll(&keyboard, &gamepad, &mouse), None);
+        keyboard.clear();
+
+        key(
+            &mut keyboard,
+            ElementState::Released,
+            VirtualKeyCode::Return,
+        );
+        assert_eq!(capture.poll(&keyboard, &gamepad, &mouse), None);
+        keyboard.clear();
+
+        key(&mut keyboard, ElementState::Pressed, VirtualKeyCode::Return);
+        assert_eq!(
+            capture.poll(&keyboard, &gamepad, &mouse),
+            Some(Captured::Binding(Binding::Key(VirtualKeyCode::Return)))
+        );
+    }
+
+    #[test]
+    fn cancel() {
+        let (mut keyboard, gamepad, mouse) =
+            (KeyboardState::new(), GamepadState::new(), MouseState::new());
+        let mut capture = InputCapture::new();
+
+        capture.start(&keyboard, &gamepad, &mouse);
+        key(&mut keyboard, ElementState::Pressed, VirtualKeyCode::Escape);
+        assert_eq!(
+            capture.poll(&keyboard, &gamepad, &mouse),
+            Some(Captured::Cancelled)
+        );
+        assert_eq!(capture.poll(&keyboard, &gamepad, &mouse), None);
+    }
+}
@@ -0,0 +1,405 @@
+use super::input::Input;
+use instant::Instant;
+use rustc_hash::FxHashSet;
+use std::{hash::Hash, time::Duration};
+
+//
+
+/// One step of a [`Combo`]
+///
+/// A step with multiple inputs is a chord.
+/// A step completes when every input is held
+/// and one of them was just pressed, or when
+/// another input of the combo was just
+/// released, like down-forward to forward.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComboStep<T> {
+    inputs: Vec<T>,
+
+    /// inputs have to be pressed in order
+    ordered: bool,
+
+    /// max time since the previous step
+    window: Option<Duration>,
+}
+
+/// Sequence of [`ComboStep`]s
+///
+/// ```no_run
+/// # use main_game_loop::prelude::*;
+/// # use winit::event::VirtualKeyCode as Key;
+/// # use std::time::Duration;
+/// let window = Duration::from_millis(300);
+/// let hadouken = Combo::new()
+///     .then(ComboStep::press(Key::Down))
+///     .then(ComboStep::chord([Key::Down, Key::Right]).within(window))
+///     .then(ComboStep::press(Key::Right).within(window))
+///     .then(ComboStep::press(Key::P).within(window));
+/// let save = Combo::new().then(ComboStep::ordered_chord([Key::LControl, Key::LShift, Key::S]));
+///
+/// let mut combos = ComboRecognizer::new()
+///     .with_combo("hadouken", hadouken)
+///     .with_combo("save", save);
+///
+/// let keyboard = KeyboardState::new();
+/// combos.update(&keyboard);
+/// if combos.just_matched("save") {
+///     // save();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Combo<T> {
+    steps: Vec<ComboStep<T>>,
+}
+
+/// Matches [`Combo`]s against the edges
+/// of any [`Input<T>`] based state
+///
+/// Call [`ComboRecognizer::update`] once per
+/// frame, before the input is cleared. Any
+/// press that doesn't fit the current step
+/// restarts the combo.
+#[derive(Debug, Clone)]
+pub struct ComboRecognizer<C, T> {
+    combos: Vec<(C, Combo<T>, Progress)>,
+    just_matched: FxHashSet<C>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Progress {
+    step: usize,
+    last: Option<Instant>,
+}
+
+//
+
+impl<T> ComboStep<T>
+where
+    T: Copy + Eq + Hash,
+{
+    pub fn press(input: T) -> Self {
+        Self::chord([input])
+    }
+
+    /// Every input held, in any order
+    pub fn chord(inputs: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            inputs: inputs.into_iter().collect(),
+            ordered: false,
+            window: None,
+        }
+    }
+
+    /// Every input held, pressed in the given order
+    pub fn ordered_chord(inputs: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            ordered: true,
+            ..Self::chord(inputs)
+        }
+    }
+
+    /// Complete this step within `window`
+    /// of the previous one
+    ///
+    /// Ignored on the first step
+    pub fn within(mut self, window: Duration) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    pub fn inputs(&self) -> &[T] {
+        &self.inputs
+    }
+
+    fn completed(&self, input: &Input<T>) -> bool {
+        if !self.inputs.iter().all(|code| input.pressed(*code)) {
+            return false;
+        }
+
+        // by press time, presses in one frame still have an order
+        !self.ordered
+            || self
+                .inputs
+                .windows(2)
+                .all(|pair| input.pressed_at(pair[0]) <= input.pressed_at(pair[1]))
+    }
+}
+
+impl<T> Default for Combo<T> {
+    fn default() -> Self {
+        Self { steps: Vec::new() }
+    }
+}
+
+impl<T> Combo<T>
+where
+    T: Copy + Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(mut self, step: ComboStep<T>) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn steps(&self) -> &[ComboStep<T>] {
+        &self.steps
+    }
+}
+
+impl<C, T> Default for ComboRecognizer<C, T> {
+    fn default() -> Self {
+        Self {
+            combos: Vec::new(),
+            just_matched: Default::default(),
+        }
+    }
+}
+
+impl<C, T> ComboRecognizer<C, T>
+where
+    C: Copy + Eq + Hash,
+    T: Copy + Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_combo(mut self, id: C, combo: Combo<T>) -> Self {
+        self.add(id, combo);
+        self
+    }
+
+    /// Add or replace the combo `id`
+    pub fn add(&mut self, id: C, combo: Combo<T>) {
+        self.remove(id);
+        self.combos.push((id, combo, Progress::default()));
+    }
+
+    pub fn remove(&mut self, id: C) {
+        self.combos.retain(|(combo_id, _, _)| *combo_id != id);
+    }
+
+    /// Restart every combo
+    pub fn reset(&mut self) {
+        for (_, _, progress) in self.combos.iter_mut() {
+            *progress = Progress::default();
+        }
+    }
+
+    pub fn update(&mut self, input: &Input<T>) {
+        self.update_at(input, Instant::now());
+    }
+
+    /// [`ComboRecognizer::update`] with
+    /// an explicit timestamp
+    pub fn update_at(&mut self, input: &Input<T>, now: Instant) {
+        self.just_matched.clear();
+        for (id, combo, progress) in self.combos.iter_mut() {
+            if Self::advance(combo, progress, input, now) {
+                self.just_matched.insert(*id);
+            }
+        }
+    }
+
+    /// Combos completed on the last update
+    #[inline]
+    pub fn just_matched(&self, id: C) -> bool {
+        self.just_matched.contains(&id)
+    }
+
+    #[inline]
+    pub fn iter_just_matched(&self) -> impl Iterator<Item = &C> {
+        self.just_matched.iter()
+    }
+
+    /// Returns true if the combo completed
+    fn advance(combo: &Combo<T>, progress: &mut Progress, input: &Input<T>, now: Instant) -> bool {
+        // too slow for the current step
+        let window = combo.steps.get(progress.step).and_then(|step| step.window);
+        if let (Some(window), Some(last)) = (window, progress.last) {
+            if now.duration_since(last) > window {
+                *progress = Progress::default();
+            }
+        }
+
+        if input.get_just_pressed().is_empty() && input.get_just_released().is_empty() {
+            return false;
+        }
+
+        // retry from the first step if a press
+        // didn't belong to the current step
+        for _ in 0..2 {
+            let Some(step) = combo.steps.get(progress.step) else {
+                return false;
+            };
+
+            let fits = input
+                .iter_just_pressed()
+                .all(|code| step.inputs.contains(code));
+            if !fits {
+                if progress.step == 0 {
+                    return false;
+                }
+                *progress = Progress::default();
+                continue;
+            }
+
+            let pressed = input
+                .iter_just_pressed()
+                .any(|code| step.inputs.contains(code));
+            let released = input.iter_just_released().any(|code| {
+                !step.inputs.contains(code) && combo.steps.iter().any(|s| s.inputs.contains(code))
+            });
+            if !(pressed || released) || !step.completed(input) {
+                return false;
+            }
+
+            progress.step += 1;
+            progress.last = Some(now);
+            if progress.step == combo.steps.len() {
+                *progress = Progress::default();
+                return true;
+            }
+            return false;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::ElementState::{self, Pressed, Released};
+
+    const DOWN: u32 = 0;
+    const RIGHT: u32 = 1;
+    const PUNCH: u32 = 2;
+    const OTHER: u32 = 3;
+
+    fn quarter_circle(window: Duration) -> Combo<u32> {
+        Combo::new()
+            .then(ComboStep::press(DOWN))
+            .then(ComboStep::chord([DOWN, RIGHT]).within(window))
+            .then(ComboStep::press(RIGHT).within(window))
+            .then(ComboStep::press(PUNCH).within(window))
+    }
+
+    /// Apply `events`, update and clear like one frame
+    fn frame(
+        combos: &mut ComboRecognizer<&'static str, u32>,
+        input: &mut Input<u32>,
+        events: &[(ElementState, u32)],
+        now: Instant,
+    ) -> bool {
+        for (state, code) in events {
+            input.event(*state, *code);
+        }
+        combos.update_at(input, now);
+        input.clear();
+        combos.just_matched("combo")
+    }
+
+    #[test]
+    fn sequence() {
+        let window = Duration::from_millis(300);
+        let mut combos = ComboRecognizer::new().with_combo("combo", quarter_circle(window));
+        let mut input = Input::new();
+        let now = Instant::now();
+
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, DOWN)], now));
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, RIGHT)], now));
+        assert!(!frame(&mut combos, &mut input, &[(Released, DOWN)], now));
+        assert!(frame(&mut combos, &mut input, &[(Pressed, PUNCH)], now));
+
+        // matched combos restart
+        assert!(!frame(&mut combos, &mut input, &[(Released, PUNCH)], now));
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, PUNCH)], now));
+    }
+
+    #[test]
+    fn chord() {
+        let mut combos = ComboRecognizer::new()
+            .with_combo("combo", Combo::new().then(ComboStep::chord([DOWN, PUNCH])));
+        let mut input = Input::new();
+        let now = Instant::now();
+
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, PUNCH)], now));
+        assert!(frame(&mut combos, &mut input, &[(Pressed, DOWN)], now));
+
+        let mut combos = ComboRecognizer::new().with_combo(
+            "combo",
+            Combo::new().then(ComboStep::ordered_chord([DOWN, PUNCH])),
+        );
+        let mut input = Input::new();
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, PUNCH)], now));
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, DOWN)], now));
+        assert!(!frame(&mut combos, &mut input, &[(Released, PUNCH)], now));
+        assert!(frame(&mut combos, &mut input, &[(Pressed, PUNCH)], now));
+    }
+
+    #[test]
+    fn ordered_chord_in_one_frame() {
+        let ordered = || {
+            ComboRecognizer::new().with_combo(
+                "combo",
+                Combo::new().then(ComboStep::ordered_chord([DOWN, PUNCH])),
+            )
+        };
+        let now = Instant::now();
+
+        // both in one frame, in the wrong order
+        let (mut combos, mut input) = (ordered(), Input::new());
+        input.event(Pressed, PUNCH);
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, DOWN)], now));
+
+        let (mut combos, mut input) = (ordered(), Input::new());
+        input.event(Pressed, DOWN);
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(frame(&mut combos, &mut input, &[(Pressed, PUNCH)], now));
+    }
+
+    #[test]
+    fn timeout() {
+        let window = Duration::from_millis(300);
+        let mut combos = ComboRecognizer::new().with_combo("combo", quarter_circle(window));
+        let mut input = Input::new();
+        let now = Instant::now();
+        let late = now + window * 2;
+
+        frame(&mut combos, &mut input, &[(Pressed, DOWN)], now);
+        frame(&mut combos, &mut input, &[(Pressed, RIGHT)], now);
+        frame(&mut combos, &mut input, &[(Released, DOWN)], now);
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, PUNCH)], late));
+    }
+
+    #[test]
+    fn wrong_press_and_reset() {
+        let mut combos = ComboRecognizer::new().with_combo(
+            "combo",
+            Combo::new()
+                .then(ComboStep::press(DOWN))
+                .then(ComboStep::press(PUNCH)),
+        );
+        let mut input = Input::new();
+        let now = Instant::now();
+
+        frame(&mut combos, &mut input, &[(Pressed, DOWN)], now);
+        frame(&mut combos, &mut input, &[(Pressed, OTHER)], now);
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, PUNCH)], now));
+
+        frame(&mut combos, &mut input, &[(Released, PUNCH)], now);
+        frame(
+            &mut combos,
+            &mut input,
+            &[(Released, DOWN), (Pressed, DOWN)],
+            now,
+        );
+        combos.reset();
+        assert!(!frame(&mut combos, &mut input, &[(Pressed, PUNCH)], now));
+    }
+}
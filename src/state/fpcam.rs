@@ -1,7 +1,6 @@
-use super::window::WindowState;
+use super::{input_event::InputEvent, window::WindowState};
 use crate::event::Event;
 use glam::{Mat4, Vec2, Vec3};
-use winit::event::DeviceEvent;
 
 //
 
@@ -93,12 +92,15 @@ impl FPCam {
 
     #[inline]
     pub fn event(&mut self, event: &Event) {
-        if let Event::DeviceEvent {
-            event: DeviceEvent::MouseMotion { delta: (x, y) },
-            ..
-        } = event
-        {
-            self.update(Vec2::new(*x as _, *y as _));
+        if let Some(event) = InputEvent::from_event(event) {
+            self.input_event(&event);
+        }
+    }
+
+    #[inline]
+    pub fn input_event(&mut self, event: &InputEvent) {
+        if let InputEvent::MouseMotion { x, y } = *event {
+            self.update(Vec2::new(x as _, y as _));
         }
     }
 
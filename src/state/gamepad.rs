@@ -1,10 +1,8 @@
-use super::input::Input;
-use crate::event::{CustomEvent, Event};
-use gilrs::EventType;
+use super::{input::Input, input_event::InputEvent};
+use crate::event::Event;
 use glam::Vec2;
 use rustc_hash::FxHashMap;
 use std::ops::{Deref, DerefMut};
-use winit::event::ElementState;
 
 //
 
@@ -39,6 +37,7 @@ pub struct GamepadState {
     axes: FxHashMap<GamepadAxisInput, f32>,
     gamepads: FxHashMap<Gamepad, DeadZone>,
     auto_clear: bool,
+    release_on_unfocus: bool,
 }
 
 //
@@ -72,67 +71,96 @@ impl GamepadState {
         self.auto_clear = auto_clear;
     }
 
+    /// Release everything when the
+    /// window loses focus, see
+    /// [`Input::release_all`]
+    #[inline]
+    pub fn with_release_on_unfocus(mut self) -> Self {
+        self.set_release_on_unfocus(true);
+        self
+    }
+
+    #[inline]
+    pub fn set_release_on_unfocus(&mut self, release_on_unfocus: bool) {
+        self.release_on_unfocus = release_on_unfocus;
+    }
+
     pub fn event(&mut self, event: &Event) {
-        match event {
+        if let Some(event) = InputEvent::from_event(event) {
+            self.input_event(&event);
+        }
+    }
+
+    pub fn input_event(&mut self, event: &InputEvent) {
+        match *event {
             // gamepad events
-            Event::UserEvent(CustomEvent::GamePadEvent(gilrs::Event { id, event, .. })) => {
-                let gamepad = *id;
+            InputEvent::GamepadButton {
+                gamepad,
+                state,
+                button,
+            } => {
+                self.gamepads.entry(gamepad).or_default();
+                let input = GamepadButtonInput { gamepad, button };
+                self.inner.event(state, input);
+            }
+            InputEvent::GamepadButtonChanged {
+                gamepad,
+                button,
+                value,
+            } => {
+                let deadzone = self.gamepads.entry(gamepad).or_default();
+                let (deadzone, axis) = match button {
+                    GamepadButton::LeftTrigger2 => (deadzone.left, GamepadAxis::LeftZ),
+                    GamepadButton::RightTrigger2 => (deadzone.right, GamepadAxis::RightZ),
+                    _ => return,
+                };
+
+                let input = GamepadAxisInput { gamepad, axis };
+                if value.abs() <= deadzone {
+                    self.axes.remove(&input);
+                } else {
+                    self.axes.insert(input, value);
+                }
+            }
+            InputEvent::GamepadAxis {
+                gamepad,
+                axis,
+                value,
+            } => {
                 let deadzone = self.gamepads.entry(gamepad).or_default();
-                match *event {
-                    EventType::ButtonPressed(button, _) => {
-                        let input = GamepadButtonInput { gamepad, button };
-                        self.inner.event(ElementState::Pressed, input);
-                    }
-                    EventType::ButtonReleased(button, _) => {
-                        let input = GamepadButtonInput { gamepad, button };
-                        self.inner.event(ElementState::Released, input);
-                    }
-                    EventType::ButtonChanged(button, val, _) => {
-                        let (deadzone, axis) = match button {
-                            GamepadButton::LeftTrigger2 => (deadzone.left, GamepadAxis::LeftZ),
-                            GamepadButton::RightTrigger2 => (deadzone.right, GamepadAxis::RightZ),
-                            _ => return,
-                        };
-
-                        let input = GamepadAxisInput { gamepad, axis };
-                        if val.abs() <= deadzone {
-                            self.axes.remove(&input);
-                        } else {
-                            self.axes.insert(input, val);
-                        }
-                    }
-                    EventType::AxisChanged(axis, val, _) => {
-                        let deadzone = match axis {
-                            GamepadAxis::LeftStickX => deadzone.left_stick.x,
-                            GamepadAxis::LeftStickY => deadzone.left_stick.y,
-                            GamepadAxis::LeftZ => deadzone.left,
-                            GamepadAxis::RightStickX => deadzone.right_stick.x,
-                            GamepadAxis::RightStickY => deadzone.right_stick.y,
-                            GamepadAxis::RightZ => deadzone.right,
-                            GamepadAxis::DPadX => deadzone.d_pad.x,
-                            GamepadAxis::DPadY => deadzone.d_pad.y,
-                            GamepadAxis::Unknown => return,
-                        };
-
-                        let input = GamepadAxisInput { gamepad, axis };
-                        if val.abs() <= deadzone {
-                            self.axes.remove(&input);
-                        } else {
-                            self.axes.insert(input, val);
-                        }
-                    }
-                    /* THIS MIGHT COME LATER THAN ACTUAL BUTTON EVENTS: EventType::Connected => {
-                        self.gamepads.insert(gamepad);
-                    } */
-                    EventType::Disconnected => {
-                        self.gamepads.remove(&gamepad);
-                    }
-                    _ => {}
+                let deadzone = match axis {
+                    GamepadAxis::LeftStickX => deadzone.left_stick.x,
+                    GamepadAxis::LeftStickY => deadzone.left_stick.y,
+                    GamepadAxis::LeftZ => deadzone.left,
+                    GamepadAxis::RightStickX => deadzone.right_stick.x,
+                    GamepadAxis::RightStickY => deadzone.right_stick.y,
+                    GamepadAxis::RightZ => deadzone.right,
+                    GamepadAxis::DPadX => deadzone.d_pad.x,
+                    GamepadAxis::DPadY => deadzone.d_pad.y,
+                    GamepadAxis::Unknown => return,
+                };
+
+                let input = GamepadAxisInput { gamepad, axis };
+                if value.abs() <= deadzone {
+                    self.axes.remove(&input);
+                } else {
+                    self.axes.insert(input, value);
                 }
             }
+            // might come later than the first button events
+            InputEvent::GamepadConnected(gamepad) => {
+                self.gamepads.entry(gamepad).or_default();
+            }
+            InputEvent::GamepadDisconnected(gamepad) => {
+                self.gamepads.remove(&gamepad);
+            }
+
+            InputEvent::Focused(false) if self.release_on_unfocus => {
+                self.inner.release_all();
+            }
 
             // auto clear event
-            Event::RedrawEventsCleared if self.auto_clear => self.inner.clear(),
+            InputEvent::RedrawEventsCleared if self.auto_clear => self.inner.clear(),
 
             _ => {}
         }
@@ -1,5 +1,6 @@
-use rustc_hash::FxHashSet;
-use std::hash::Hash;
+use instant::Instant;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{hash::Hash, time::Duration};
 use winit::event::ElementState;
 
 //
@@ -9,6 +10,22 @@ pub struct Input<T> {
     pressed: FxHashSet<T>,
     just_pressed: FxHashSet<T>,
     just_released: FxHashSet<T>,
+
+    /// latest press and the one before it
+    pressed_at: FxHashMap<T, (Instant, Option<Instant>)>,
+    released_at: FxHashMap<T, Instant>,
+
+    /// released by [`Input::release_all`],
+    /// their real release is ignored
+    released_early: FxHashSet<T>,
+}
+
+/// Tap or hold classification of a press,
+/// see [`Input::press_kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PressKind {
+    Tap,
+    Hold,
 }
 
 //
@@ -22,6 +39,9 @@ where
             pressed: Default::default(),
             just_pressed: Default::default(),
             just_released: Default::default(),
+            pressed_at: Default::default(),
+            released_at: Default::default(),
+            released_early: Default::default(),
         }
     }
 }
@@ -89,6 +109,72 @@ where
         &self.just_released
     }
 
+    // timing
+    // when inputs were pressed and released
+
+    #[inline]
+    pub fn pressed_at(&self, code: T) -> Option<Instant> {
+        self.pressed_at.get(&code).map(|(at, _)| *at)
+    }
+
+    #[inline]
+    pub fn released_at(&self, code: T) -> Option<Instant> {
+        self.released_at.get(&code).copied()
+    }
+
+    /// How long `code` has been held,
+    /// `None` if it isn't pressed
+    pub fn held_for(&self, code: T) -> Option<Duration> {
+        if !self.pressed(code) {
+            return None;
+        }
+        self.pressed_at(code).map(|at| at.elapsed())
+    }
+
+    /// `code` has been held for at least `threshold`
+    pub fn long_pressed(&self, code: T, threshold: Duration) -> bool {
+        self.held_for(code)
+            .is_some_and(|held_for| held_for >= threshold)
+    }
+
+    /// `code` was just pressed within
+    /// `window` of its previous press
+    pub fn double_tapped(&self, code: T, window: Duration) -> bool {
+        if !self.just_pressed(code) {
+            return false;
+        }
+        match self.pressed_at.get(&code) {
+            Some((at, Some(previous))) => at.duration_since(*previous) <= window,
+            _ => false,
+        }
+    }
+
+    /// Classify the press of `code`
+    ///
+    /// [`PressKind::Tap`] once, when it is released
+    /// before `hold_threshold`. [`PressKind::Hold`]
+    /// while it is held past `hold_threshold` and
+    /// when it is released after that. `None`
+    /// while undecided or not pressed.
+    pub fn press_kind(&self, code: T, hold_threshold: Duration) -> Option<PressKind> {
+        let pressed_at = self.pressed_at(code)?;
+        let held_for = if self.pressed(code) {
+            pressed_at.elapsed()
+        } else if self.just_released(code) {
+            self.released_at(code)?.duration_since(pressed_at)
+        } else {
+            return None;
+        };
+
+        if held_for >= hold_threshold {
+            Some(PressKind::Hold)
+        } else if self.pressed(code) {
+            None
+        } else {
+            Some(PressKind::Tap)
+        }
+    }
+
     //
 
     #[inline]
@@ -102,17 +188,178 @@ where
         self.just_released.clear();
     }
 
+    /// Release every pressed input now
+    ///
+    /// For releases that will never arrive, like
+    /// after the window lost focus. The real
+    /// release is ignored if it still comes.
+    ///
+    /// Nothing is pressed again when focus
+    /// returns. winit sends synthetic presses
+    /// for keys still held, other inputs count
+    /// as pressed from their next press event.
+    pub fn release_all(&mut self) {
+        let now = Instant::now();
+        for code in self.pressed.drain() {
+            self.just_released.insert(code);
+            self.released_at.insert(code, now);
+            self.released_early.insert(code);
+        }
+    }
+
     pub(crate) fn event(&mut self, state: ElementState, code: T) {
         // self.clear();
         match state {
+            ElementState::Released if self.released_early.remove(&code) => {}
             ElementState::Pressed => {
-                self.pressed.insert(code);
+                self.released_early.remove(&code);
+                // key repeats keep the original press time
+                if self.pressed.insert(code) {
+                    let now = Instant::now();
+                    let previous = self.pressed_at(code);
+                    self.pressed_at.insert(code, (now, previous));
+                }
                 self.just_pressed.insert(code);
             }
             ElementState::Released => {
                 self.pressed.remove(&code);
                 self.just_released.insert(code);
+                self.released_at.insert(code, Instant::now());
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use ElementState::{Pressed, Released};
+
+    const A: u32 = 30;
+
+    /// press `code`, recorded as pressed at `at`
+    fn press_at(input: &mut Input<u32>, code: u32, at: Instant) {
+        input.event(Pressed, code);
+        input.pressed_at.get_mut(&code).unwrap().0 = at;
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn double_tap_window() {
+        let base = Instant::now();
+        for (second, expected) in [(ms(299), true), (ms(300), true), (ms(301), false)] {
+            let mut input = Input::new();
+            press_at(&mut input, A, base);
+            input.event(Released, A);
+            input.clear();
+
+            press_at(&mut input, A, base + second);
+            assert_eq!(input.double_tapped(A, ms(300)), expected, "{second:?}");
+            assert_eq!(input.pressed_at(A), Some(base + second));
+        }
+
+        // only on the frame of the press
+        let mut input = Input::new();
+        press_at(&mut input, A, base);
+        input.event(Released, A);
+        press_at(&mut input, A, base + ms(100));
+        input.clear();
+        assert!(!input.double_tapped(A, ms(300)));
+    }
+
+    #[test]
+    fn previous_press() {
+        let base = Instant::now();
+        let mut input = Input::new();
+        press_at(&mut input, A, base);
+        assert_eq!(input.pressed_at.get(&A), Some(&(base, None)));
+
+        // key repeats keep the original press
+        input.event(Pressed, A);
+        assert_eq!(input.pressed_at(A), Some(base));
+
+        input.event(Released, A);
+        press_at(&mut input, A, base + ms(500));
+        assert_eq!(
+            input.pressed_at.get(&A),
+            Some(&(base + ms(500), Some(base)))
+        );
+    }
+
+    #[test]
+    fn long_press_while_held() {
+        let mut input = Input::new();
+        assert_eq!(input.held_for(A), None);
+
+        input.event(Pressed, A);
+        thread::sleep(ms(20));
+        assert!(input.held_for(A).unwrap() >= ms(20));
+        assert!(input.long_pressed(A, ms(20)));
+        assert!(!input.long_pressed(A, Duration::from_secs(3600)));
+        assert_eq!(input.press_kind(A, ms(20)), Some(PressKind::Hold));
+        assert_eq!(input.press_kind(A, Duration::from_secs(3600)), None);
+
+        input.event(Released, A);
+        assert_eq!(input.held_for(A), None);
+        assert!(!input.long_pressed(A, ms(20)));
+    }
+
+    #[test]
+    fn press_kind_on_release() {
+        let base = Instant::now();
+        let release = |held: Duration| {
+            let mut input = Input::new();
+            press_at(&mut input, A, base);
+            input.event(Released, A);
+            input.released_at.insert(A, base + held);
+            input
+        };
+
+        assert_eq!(
+            release(ms(100)).press_kind(A, ms(200)),
+            Some(PressKind::Tap)
+        );
+        assert_eq!(
+            release(ms(200)).press_kind(A, ms(200)),
+            Some(PressKind::Hold)
+        );
+
+        // reported once
+        let mut input = release(ms(100));
+        input.clear();
+        assert_eq!(input.press_kind(A, ms(200)), None);
+    }
+
+    #[test]
+    fn released_early() {
+        let mut input = Input::new();
+        input.event(Pressed, A);
+        input.clear();
+
+        input.release_all();
+        assert!(!input.pressed(A));
+        assert!(input.just_released(A));
+        assert!(input.released_at(A).is_some());
+
+        // the real release is ignored once
+        input.clear();
+        input.event(Released, A);
+        assert!(!input.just_released(A));
+        input.event(Released, A);
+        assert!(input.just_released(A));
+
+        // a new press clears it
+        let mut input = Input::new();
+        input.event(Pressed, A);
+        input.release_all();
+        input.event(Pressed, A);
+        input.clear();
+        input.event(Released, A);
+        assert!(input.just_released(A));
+        assert!(!input.pressed(A));
+    }
+}
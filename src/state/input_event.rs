@@ -0,0 +1,203 @@
+use super::{
+    gamepad::{Gamepad, GamepadAxis, GamepadButton},
+    keyboard::ModifiersState,
+    mouse::MouseButton,
+    position::ScanCode,
+    touch::TouchPhase,
+};
+use crate::event::{CustomEvent, Event};
+use gilrs::EventType;
+use winit::event::{
+    DeviceEvent, ElementState, Ime, KeyboardInput, MouseScrollDelta, Touch, VirtualKeyCode,
+    WindowEvent,
+};
+
+//
+
+/// Every input the states react to,
+/// without window or device ids
+///
+/// The states convert winit events into
+/// these in `event` and handle them in
+/// `input_event`, which can also be fed
+/// directly, like when replaying a recording.
+///
+/// Variant order is part of the recording
+/// file format, new variants go at the end.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputEvent {
+    // keyboard
+    Key {
+        state: ElementState,
+        scancode: ScanCode,
+        virtual_keycode: Option<VirtualKeyCode>,
+    },
+    Modifiers(ModifiersState),
+    ReceivedCharacter(char),
+    Ime(Ime),
+
+    // mouse
+    MouseButton {
+        state: ElementState,
+        button: MouseButton,
+    },
+    MouseWheelLines {
+        x: f32,
+        y: f32,
+    },
+    MouseWheelPixels {
+        x: f64,
+        y: f64,
+    },
+    MouseMotion {
+        x: f64,
+        y: f64,
+    },
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    CursorEntered,
+    CursorLeft,
+
+    // gamepad
+    GamepadButton {
+        gamepad: Gamepad,
+        state: ElementState,
+        button: GamepadButton,
+    },
+    GamepadButtonChanged {
+        gamepad: Gamepad,
+        button: GamepadButton,
+        value: f32,
+    },
+    GamepadAxis {
+        gamepad: Gamepad,
+        axis: GamepadAxis,
+        value: f32,
+    },
+    GamepadConnected(Gamepad),
+    GamepadDisconnected(Gamepad),
+
+    // window
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    Focused(bool),
+    CloseRequested,
+
+    /// end of a frame, `auto_clear` clears on this
+    RedrawEventsCleared,
+
+    // touch
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+    },
+}
+
+//
+
+impl InputEvent {
+    /// `None` for events no state reacts to
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let event = match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state,
+                            scancode,
+                            virtual_keycode,
+                            ..
+                        },
+                    ..
+                } => Self::Key {
+                    state: *state,
+                    scancode: *scancode,
+                    virtual_keycode: *virtual_keycode,
+                },
+                WindowEvent::ModifiersChanged(modifiers) => Self::Modifiers(*modifiers),
+                WindowEvent::ReceivedCharacter(c) => Self::ReceivedCharacter(*c),
+                WindowEvent::Ime(ime) => Self::Ime(ime.clone()),
+                WindowEvent::MouseInput { state, button, .. } => Self::MouseButton {
+                    state: *state,
+                    button: *button,
+                },
+                WindowEvent::MouseWheel { delta, .. } => match *delta {
+                    MouseScrollDelta::LineDelta(x, y) => Self::MouseWheelLines { x, y },
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        Self::MouseWheelPixels { x: pos.x, y: pos.y }
+                    }
+                },
+                WindowEvent::CursorMoved { position, .. } => Self::CursorMoved {
+                    x: position.x,
+                    y: position.y,
+                },
+                WindowEvent::Touch(Touch {
+                    id,
+                    phase,
+                    location,
+                    ..
+                }) => Self::Touch {
+                    id: *id,
+                    phase: *phase,
+                    x: location.x,
+                    y: location.y,
+                },
+                WindowEvent::CursorEntered { .. } => Self::CursorEntered,
+                WindowEvent::CursorLeft { .. } => Self::CursorLeft,
+                WindowEvent::Resized(size) => Self::Resized {
+                    width: size.width,
+                    height: size.height,
+                },
+                WindowEvent::Focused(focused) => Self::Focused(*focused),
+                WindowEvent::CloseRequested => Self::CloseRequested,
+                _ => return None,
+            },
+
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta: (x, y) },
+                ..
+            } => Self::MouseMotion { x: *x, y: *y },
+
+            Event::UserEvent(CustomEvent::GamePadEvent(gilrs::Event { id, event, .. })) => {
+                let gamepad = *id;
+                match *event {
+                    EventType::ButtonPressed(button, _) => Self::GamepadButton {
+                        gamepad,
+                        state: ElementState::Pressed,
+                        button,
+                    },
+                    EventType::ButtonReleased(button, _) => Self::GamepadButton {
+                        gamepad,
+                        state: ElementState::Released,
+                        button,
+                    },
+                    EventType::ButtonChanged(button, value, _) => Self::GamepadButtonChanged {
+                        gamepad,
+                        button,
+                        value,
+                    },
+                    EventType::AxisChanged(axis, value, _) => Self::GamepadAxis {
+                        gamepad,
+                        axis,
+                        value,
+                    },
+                    EventType::Connected => Self::GamepadConnected(gamepad),
+                    EventType::Disconnected => Self::GamepadDisconnected(gamepad),
+                    _ => return None,
+                }
+            }
+
+            Event::RedrawEventsCleared => Self::RedrawEventsCleared,
+
+            _ => return None,
+        };
+        Some(event)
+    }
+}
@@ -1,14 +1,29 @@
-use super::input::Input;
+use super::{
+    input::Input,
+    input_event::InputEvent,
+    position::{KeyPosition, ScanCode},
+};
 use crate::event::Event;
 use std::ops::{Deref, DerefMut};
-use winit::event::{KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::event::VirtualKeyCode;
+
+//
+
+pub use winit::event::ModifiersState;
 
 //
 
 #[derive(Debug, Clone, Default)]
 pub struct KeyboardState {
     inner: Input<VirtualKeyCode>,
+
+    /// physical keys, including the
+    /// ones without a virtual keycode
+    scancodes: Input<ScanCode>,
+
+    modifiers: ModifiersState,
     auto_clear: bool,
+    release_on_unfocus: bool,
 }
 
 //
@@ -30,31 +45,142 @@ impl KeyboardState {
         self.auto_clear = auto_clear;
     }
 
+    /// Release everything when the
+    /// window loses focus, see
+    /// [`Input::release_all`]
+    #[inline]
+    pub fn with_release_on_unfocus(mut self) -> Self {
+        self.set_release_on_unfocus(true);
+        self
+    }
+
+    #[inline]
+    pub fn set_release_on_unfocus(&mut self, release_on_unfocus: bool) {
+        self.release_on_unfocus = release_on_unfocus;
+    }
+
     pub fn event(&mut self, event: &Event) {
-        match event {
+        if let Some(event) = InputEvent::from_event(event) {
+            self.input_event(&event);
+        }
+    }
+
+    pub fn input_event(&mut self, event: &InputEvent) {
+        match *event {
             // kb events
-            Event::WindowEvent {
-                event:
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state,
-                                virtual_keycode: Some(virtual_keycode),
-                                ..
-                            },
-                        ..
-                    },
-                ..
+            InputEvent::Key {
+                state,
+                scancode,
+                virtual_keycode,
             } => {
-                self.inner.event(*state, *virtual_keycode);
+                self.scancodes.event(state, scancode);
+                if let Some(virtual_keycode) = virtual_keycode {
+                    self.inner.event(state, virtual_keycode);
+                }
+            }
+
+            // modifier events
+            InputEvent::Modifiers(modifiers) => {
+                self.modifiers = modifiers;
+            }
+
+            InputEvent::Focused(false) => {
+                self.modifiers = ModifiersState::empty();
+                if self.release_on_unfocus {
+                    self.inner.release_all();
+                    self.scancodes.release_all();
+                }
             }
 
             // auto clear event
-            Event::RedrawEventsCleared if self.auto_clear => self.inner.clear(),
+            InputEvent::RedrawEventsCleared if self.auto_clear => self.clear(),
 
             _ => {}
         }
     }
+
+    /// Clear just pressed and just released
+    /// virtual keys and scancodes
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.scancodes.clear();
+    }
+
+    // physical keys
+
+    #[inline]
+    pub fn scancodes(&self) -> &Input<ScanCode> {
+        &self.scancodes
+    }
+
+    #[inline]
+    pub fn pressed_position(&self, position: KeyPosition) -> bool {
+        position
+            .scancode()
+            .is_some_and(|scancode| self.scancodes.pressed(scancode))
+    }
+
+    #[inline]
+    pub fn just_pressed_position(&self, position: KeyPosition) -> bool {
+        position
+            .scancode()
+            .is_some_and(|scancode| self.scancodes.just_pressed(scancode))
+    }
+
+    #[inline]
+    pub fn just_released_position(&self, position: KeyPosition) -> bool {
+        position
+            .scancode()
+            .is_some_and(|scancode| self.scancodes.just_released(scancode))
+    }
+
+    // modifiers
+
+    #[inline]
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Either control key
+    #[inline]
+    pub fn ctrl(&self) -> bool {
+        self.modifiers.ctrl()
+    }
+
+    /// Either shift key
+    #[inline]
+    pub fn shift(&self) -> bool {
+        self.modifiers.shift()
+    }
+
+    /// Either alt key
+    #[inline]
+    pub fn alt(&self) -> bool {
+        self.modifiers.alt()
+    }
+
+    /// Either logo key: windows, command or super
+    #[inline]
+    pub fn logo(&self) -> bool {
+        self.modifiers.logo()
+    }
+
+    /// `code` is pressed while exactly
+    /// `modifiers` are held
+    ///
+    /// `ctrl+s` doesn't match `ctrl+shift+s`
+    #[inline]
+    pub fn pressed_with(&self, code: VirtualKeyCode, modifiers: ModifiersState) -> bool {
+        self.modifiers == modifiers && self.pressed(code)
+    }
+
+    /// `code` was just pressed while
+    /// exactly `modifiers` are held
+    #[inline]
+    pub fn just_pressed_with(&self, code: VirtualKeyCode, modifiers: ModifiersState) -> bool {
+        self.modifiers == modifiers && self.just_pressed(code)
+    }
 }
 
 impl Deref for KeyboardState {
@@ -70,3 +196,64 @@ impl DerefMut for KeyboardState {
         &mut self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::ElementState::{self, Pressed, Released};
+
+    fn key(keyboard: &mut KeyboardState, state: ElementState, key: VirtualKeyCode) {
+        keyboard.input_event(&InputEvent::Key {
+            state,
+            scancode: 0,
+            virtual_keycode: Some(key),
+        });
+    }
+
+    #[test]
+    fn exact_modifiers() {
+        let mut keyboard = KeyboardState::new();
+        let ctrl_shift = ModifiersState::CTRL | ModifiersState::SHIFT;
+
+        keyboard.input_event(&InputEvent::Modifiers(ModifiersState::CTRL));
+        key(&mut keyboard, Pressed, VirtualKeyCode::S);
+        assert!(keyboard.just_pressed_with(VirtualKeyCode::S, ModifiersState::CTRL));
+        assert!(!keyboard.just_pressed_with(VirtualKeyCode::S, ctrl_shift));
+        assert!(!keyboard.just_pressed_with(VirtualKeyCode::S, ModifiersState::empty()));
+
+        keyboard.clear();
+        keyboard.input_event(&InputEvent::Modifiers(ctrl_shift));
+        assert!(!keyboard.pressed_with(VirtualKeyCode::S, ModifiersState::CTRL));
+        assert!(keyboard.pressed_with(VirtualKeyCode::S, ctrl_shift));
+        assert!(!keyboard.just_pressed_with(VirtualKeyCode::S, ctrl_shift));
+    }
+
+    #[test]
+    fn focus_loss() {
+        let mut keyboard = KeyboardState::new();
+        keyboard.input_event(&InputEvent::Modifiers(ModifiersState::ALT));
+        key(&mut keyboard, Pressed, VirtualKeyCode::Tab);
+        keyboard.input_event(&InputEvent::Focused(false));
+
+        // modifiers always reset, keys only when asked to
+        assert_eq!(keyboard.modifiers(), ModifiersState::empty());
+        assert!(keyboard.pressed(VirtualKeyCode::Tab));
+
+        let mut keyboard = KeyboardState::new().with_release_on_unfocus();
+        keyboard.input_event(&InputEvent::Key {
+            state: Pressed,
+            scancode: 15,
+            virtual_keycode: Some(VirtualKeyCode::Tab),
+        });
+        keyboard.clear();
+        keyboard.input_event(&InputEvent::Focused(false));
+        assert!(!keyboard.pressed(VirtualKeyCode::Tab));
+        assert!(keyboard.just_released(VirtualKeyCode::Tab));
+        assert!(keyboard.scancodes().just_released(15));
+
+        // the release arriving later is ignored
+        keyboard.clear();
+        key(&mut keyboard, Released, VirtualKeyCode::Tab);
+        assert!(!keyboard.just_released(VirtualKeyCode::Tab));
+    }
+}
@@ -1,5 +1,17 @@
+pub mod action;
+pub mod axis;
+pub mod capture;
+pub mod combo;
 pub mod fpcam;
 pub mod gamepad;
 pub mod input;
+pub mod input_event;
 pub mod keyboard;
+pub mod mouse;
+pub mod position;
+#[cfg(feature = "record")]
+pub mod record;
+pub mod text;
+pub mod tick;
+pub mod touch;
 pub mod window;
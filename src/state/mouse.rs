@@ -0,0 +1,219 @@
+use super::{input::Input, input_event::InputEvent};
+use crate::event::Event;
+use glam::Vec2;
+use std::ops::{Deref, DerefMut};
+
+//
+
+pub use winit::event::MouseButton;
+
+//
+
+/// Mouse buttons, wheel, raw motion
+/// and cursor movement
+///
+/// Deltas are accumulated until
+/// [`MouseState::clear`], which `auto_clear`
+/// calls after every redraw.
+#[derive(Debug, Clone, Default)]
+pub struct MouseState {
+    inner: Input<MouseButton>,
+
+    /// raw mouse motion, not affected
+    /// by cursor acceleration or the
+    /// window edges
+    motion: Vec2,
+
+    /// wheel delta in lines
+    wheel_lines: Vec2,
+
+    /// wheel delta in pixels,
+    /// from touchpads mostly
+    wheel_pixels: Vec2,
+
+    cursor_pos: Option<Vec2>,
+    cursor_delta: Vec2,
+
+    auto_clear: bool,
+    release_on_unfocus: bool,
+}
+
+//
+
+impl MouseState {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_auto_clear(mut self) -> Self {
+        self.set_auto_clear(true);
+        self
+    }
+
+    #[inline]
+    pub fn set_auto_clear(&mut self, auto_clear: bool) {
+        self.auto_clear = auto_clear;
+    }
+
+    /// Release everything when the
+    /// window loses focus, see
+    /// [`Input::release_all`]
+    #[inline]
+    pub fn with_release_on_unfocus(mut self) -> Self {
+        self.set_release_on_unfocus(true);
+        self
+    }
+
+    #[inline]
+    pub fn set_release_on_unfocus(&mut self, release_on_unfocus: bool) {
+        self.release_on_unfocus = release_on_unfocus;
+    }
+
+    pub fn event(&mut self, event: &Event) {
+        if let Some(event) = InputEvent::from_event(event) {
+            self.input_event(&event);
+        }
+    }
+
+    pub fn input_event(&mut self, event: &InputEvent) {
+        match *event {
+            // button events
+            InputEvent::MouseButton { state, button } => {
+                self.inner.event(state, button);
+            }
+
+            // wheel events
+            InputEvent::MouseWheelLines { x, y } => self.wheel_lines += Vec2::new(x, y),
+            InputEvent::MouseWheelPixels { x, y } => self.wheel_pixels += Vec2::new(x as _, y as _),
+
+            // cursor events
+            InputEvent::CursorMoved { x, y } => {
+                let position = Vec2::new(x as _, y as _);
+                if let Some(last) = self.cursor_pos {
+                    self.cursor_delta += position - last;
+                }
+                self.cursor_pos = Some(position);
+            }
+            InputEvent::CursorLeft => {
+                self.cursor_pos = None;
+            }
+
+            // raw motion events
+            InputEvent::MouseMotion { x, y } => {
+                self.motion += Vec2::new(x as _, y as _);
+            }
+
+            InputEvent::Focused(false) if self.release_on_unfocus => {
+                self.inner.release_all();
+            }
+
+            // auto clear event
+            InputEvent::RedrawEventsCleared if self.auto_clear => self.clear(),
+
+            _ => {}
+        }
+    }
+
+    /// Raw mouse motion since the last clear
+    #[inline]
+    pub fn motion(&self) -> Vec2 {
+        self.motion
+    }
+
+    /// Wheel lines scrolled since the last clear
+    #[inline]
+    pub fn wheel_lines(&self) -> Vec2 {
+        self.wheel_lines
+    }
+
+    /// Wheel pixels scrolled since the last clear
+    #[inline]
+    pub fn wheel_pixels(&self) -> Vec2 {
+        self.wheel_pixels
+    }
+
+    /// Last cursor position in physical pixels,
+    /// `None` if the cursor left the window
+    #[inline]
+    pub fn cursor_pos(&self) -> Option<Vec2> {
+        self.cursor_pos
+    }
+
+    /// Cursor movement since the last clear
+    #[inline]
+    pub fn cursor_delta(&self) -> Vec2 {
+        self.cursor_delta
+    }
+
+    /// Clear just pressed, just released
+    /// and every accumulated delta
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.motion = Vec2::ZERO;
+        self.wheel_lines = Vec2::ZERO;
+        self.wheel_pixels = Vec2::ZERO;
+        self.cursor_delta = Vec2::ZERO;
+    }
+}
+
+impl Deref for MouseState {
+    type Target = Input<MouseButton>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl DerefMut for MouseState {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deltas() {
+        let mut mouse = MouseState::new().with_auto_clear();
+        mouse.input_event(&InputEvent::MouseMotion { x: 1.0, y: -2.0 });
+        mouse.input_event(&InputEvent::MouseMotion { x: 3.0, y: 0.5 });
+        mouse.input_event(&InputEvent::MouseWheelLines { x: 0.0, y: 1.0 });
+        mouse.input_event(&InputEvent::MouseWheelLines { x: 0.0, y: 2.0 });
+        mouse.input_event(&InputEvent::MouseWheelPixels { x: 4.0, y: -8.0 });
+
+        assert_eq!(mouse.motion(), Vec2::new(4.0, -1.5));
+        assert_eq!(mouse.wheel_lines(), Vec2::new(0.0, 3.0));
+        assert_eq!(mouse.wheel_pixels(), Vec2::new(4.0, -8.0));
+
+        mouse.input_event(&InputEvent::RedrawEventsCleared);
+        assert_eq!(mouse.motion(), Vec2::ZERO);
+        assert_eq!(mouse.wheel_lines(), Vec2::ZERO);
+        assert_eq!(mouse.wheel_pixels(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn cursor_delta() {
+        let mut mouse = MouseState::new();
+
+        // no delta from entering the window
+        mouse.input_event(&InputEvent::CursorMoved { x: 10.0, y: 10.0 });
+        assert_eq!(mouse.cursor_delta(), Vec2::ZERO);
+
+        mouse.input_event(&InputEvent::CursorMoved { x: 15.0, y: 8.0 });
+        mouse.input_event(&InputEvent::CursorMoved { x: 20.0, y: 8.0 });
+        assert_eq!(mouse.cursor_delta(), Vec2::new(10.0, -2.0));
+        assert_eq!(mouse.cursor_pos(), Some(Vec2::new(20.0, 8.0)));
+
+        // or from coming back somewhere else
+        mouse.clear();
+        mouse.input_event(&InputEvent::CursorLeft);
+        assert_eq!(mouse.cursor_pos(), None);
+        mouse.input_event(&InputEvent::CursorMoved { x: 100.0, y: 0.0 });
+        assert_eq!(mouse.cursor_delta(), Vec2::ZERO);
+    }
+}
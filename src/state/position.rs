@@ -0,0 +1,366 @@
+pub use winit::event::ScanCode;
+
+//
+
+/// Physical key position, named after
+/// the key on a US QWERTY layout
+///
+/// `KeyW` is the key below `Digit2`
+/// on every layout, `Z` on AZERTY.
+/// Names follow the W3C `KeyboardEvent.code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyPosition {
+    Escape,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    Digit0,
+    Minus,
+    Equal,
+    Backspace,
+
+    Tab,
+    KeyQ,
+    KeyW,
+    KeyE,
+    KeyR,
+    KeyT,
+    KeyY,
+    KeyU,
+    KeyI,
+    KeyO,
+    KeyP,
+    BracketLeft,
+    BracketRight,
+    Enter,
+
+    CapsLock,
+    KeyA,
+    KeyS,
+    KeyD,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyJ,
+    KeyK,
+    KeyL,
+    Semicolon,
+    Quote,
+    Backquote,
+    Backslash,
+
+    ShiftLeft,
+    KeyZ,
+    KeyX,
+    KeyC,
+    KeyV,
+    KeyB,
+    KeyN,
+    KeyM,
+    Comma,
+    Period,
+    Slash,
+    ShiftRight,
+
+    ControlLeft,
+    AltLeft,
+    Space,
+    AltRight,
+    ControlRight,
+
+    ArrowUp,
+    ArrowLeft,
+    ArrowDown,
+    ArrowRight,
+}
+
+//
+
+impl KeyPosition {
+    /// Position of a platform scancode,
+    /// `None` for keys outside the table
+    /// and on platforms without one
+    pub fn from_scancode(scancode: ScanCode) -> Option<Self> {
+        SCANCODES
+            .iter()
+            .find(|(_, code)| *code == scancode)
+            .map(|(position, _)| *position)
+    }
+
+    /// Platform scancode of this position,
+    /// `None` on platforms without a table:
+    /// anything but linux, the bsds, windows
+    /// and macos
+    pub fn scancode(self) -> Option<ScanCode> {
+        SCANCODES
+            .iter()
+            .find(|(position, _)| *position == self)
+            .map(|(_, code)| *code)
+    }
+}
+
+// evdev codes on linux and the bsds, set 1
+// scancodes on windows, they only differ for
+// the extended keys
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+))]
+const SCANCODES: &[(KeyPosition, ScanCode)] = &[
+    (KeyPosition::Escape, 1),
+    (KeyPosition::Digit1, 2),
+    (KeyPosition::Digit2, 3),
+    (KeyPosition::Digit3, 4),
+    (KeyPosition::Digit4, 5),
+    (KeyPosition::Digit5, 6),
+    (KeyPosition::Digit6, 7),
+    (KeyPosition::Digit7, 8),
+    (KeyPosition::Digit8, 9),
+    (KeyPosition::Digit9, 10),
+    (KeyPosition::Digit0, 11),
+    (KeyPosition::Minus, 12),
+    (KeyPosition::Equal, 13),
+    (KeyPosition::Backspace, 14),
+    (KeyPosition::Tab, 15),
+    (KeyPosition::KeyQ, 16),
+    (KeyPosition::KeyW, 17),
+    (KeyPosition::KeyE, 18),
+    (KeyPosition::KeyR, 19),
+    (KeyPosition::KeyT, 20),
+    (KeyPosition::KeyY, 21),
+    (KeyPosition::KeyU, 22),
+    (KeyPosition::KeyI, 23),
+    (KeyPosition::KeyO, 24),
+    (KeyPosition::KeyP, 25),
+    (KeyPosition::BracketLeft, 26),
+    (KeyPosition::BracketRight, 27),
+    (KeyPosition::Enter, 28),
+    (KeyPosition::ControlLeft, 29),
+    (KeyPosition::KeyA, 30),
+    (KeyPosition::KeyS, 31),
+    (KeyPosition::KeyD, 32),
+    (KeyPosition::KeyF, 33),
+    (KeyPosition::KeyG, 34),
+    (KeyPosition::KeyH, 35),
+    (KeyPosition::KeyJ, 36),
+    (KeyPosition::KeyK, 37),
+    (KeyPosition::KeyL, 38),
+    (KeyPosition::Semicolon, 39),
+    (KeyPosition::Quote, 40),
+    (KeyPosition::Backquote, 41),
+    (KeyPosition::ShiftLeft, 42),
+    (KeyPosition::Backslash, 43),
+    (KeyPosition::KeyZ, 44),
+    (KeyPosition::KeyX, 45),
+    (KeyPosition::KeyC, 46),
+    (KeyPosition::KeyV, 47),
+    (KeyPosition::KeyB, 48),
+    (KeyPosition::KeyN, 49),
+    (KeyPosition::KeyM, 50),
+    (KeyPosition::Comma, 51),
+    (KeyPosition::Period, 52),
+    (KeyPosition::Slash, 53),
+    (KeyPosition::ShiftRight, 54),
+    (KeyPosition::AltLeft, 56),
+    (KeyPosition::Space, 57),
+    (KeyPosition::CapsLock, 58),
+    #[cfg(not(target_os = "windows"))]
+    (KeyPosition::ControlRight, 97),
+    #[cfg(not(target_os = "windows"))]
+    (KeyPosition::AltRight, 100),
+    #[cfg(not(target_os = "windows"))]
+    (KeyPosition::ArrowUp, 103),
+    #[cfg(not(target_os = "windows"))]
+    (KeyPosition::ArrowLeft, 105),
+    #[cfg(not(target_os = "windows"))]
+    (KeyPosition::ArrowRight, 106),
+    #[cfg(not(target_os = "windows"))]
+    (KeyPosition::ArrowDown, 108),
+    #[cfg(target_os = "windows")]
+    (KeyPosition::ControlRight, 0xE01D),
+    #[cfg(target_os = "windows")]
+    (KeyPosition::AltRight, 0xE038),
+    #[cfg(target_os = "windows")]
+    (KeyPosition::ArrowUp, 0xE048),
+    #[cfg(target_os = "windows")]
+    (KeyPosition::ArrowLeft, 0xE04B),
+    #[cfg(target_os = "windows")]
+    (KeyPosition::ArrowRight, 0xE04D),
+    #[cfg(target_os = "windows")]
+    (KeyPosition::ArrowDown, 0xE050),
+];
+
+// macos virtual key codes (kVK_*)
+
+#[cfg(target_os = "macos")]
+const SCANCODES: &[(KeyPosition, ScanCode)] = &[
+    (KeyPosition::KeyA, 0x00),
+    (KeyPosition::KeyS, 0x01),
+    (KeyPosition::KeyD, 0x02),
+    (KeyPosition::KeyF, 0x03),
+    (KeyPosition::KeyH, 0x04),
+    (KeyPosition::KeyG, 0x05),
+    (KeyPosition::KeyZ, 0x06),
+    (KeyPosition::KeyX, 0x07),
+    (KeyPosition::KeyC, 0x08),
+    (KeyPosition::KeyV, 0x09),
+    (KeyPosition::KeyB, 0x0B),
+    (KeyPosition::KeyQ, 0x0C),
+    (KeyPosition::KeyW, 0x0D),
+    (KeyPosition::KeyE, 0x0E),
+    (KeyPosition::KeyR, 0x0F),
+    (KeyPosition::KeyY, 0x10),
+    (KeyPosition::KeyT, 0x11),
+    (KeyPosition::Digit1, 0x12),
+    (KeyPosition::Digit2, 0x13),
+    (KeyPosition::Digit3, 0x14),
+    (KeyPosition::Digit4, 0x15),
+    (KeyPosition::Digit6, 0x16),
+    (KeyPosition::Digit5, 0x17),
+    (KeyPosition::Equal, 0x18),
+    (KeyPosition::Digit9, 0x19),
+    (KeyPosition::Digit7, 0x1A),
+    (KeyPosition::Minus, 0x1B),
+    (KeyPosition::Digit8, 0x1C),
+    (KeyPosition::Digit0, 0x1D),
+    (KeyPosition::BracketRight, 0x1E),
+    (KeyPosition::KeyO, 0x1F),
+    (KeyPosition::KeyU, 0x20),
+    (KeyPosition::BracketLeft, 0x21),
+    (KeyPosition::KeyI, 0x22),
+    (KeyPosition::KeyP, 0x23),
+    (KeyPosition::Enter, 0x24),
+    (KeyPosition::KeyL, 0x25),
+    (KeyPosition::KeyJ, 0x26),
+    (KeyPosition::Quote, 0x27),
+    (KeyPosition::KeyK, 0x28),
+    (KeyPosition::Semicolon, 0x29),
+    (KeyPosition::Backslash, 0x2A),
+    (KeyPosition::Comma, 0x2B),
+    (KeyPosition::Slash, 0x2C),
+    (KeyPosition::KeyN, 0x2D),
+    (KeyPosition::KeyM, 0x2E),
+    (KeyPosition::Period, 0x2F),
+    (KeyPosition::Tab, 0x30),
+    (KeyPosition::Space, 0x31),
+    (KeyPosition::Backquote, 0x32),
+    (KeyPosition::Backspace, 0x33),
+    (KeyPosition::Escape, 0x35),
+    (KeyPosition::ShiftLeft, 0x38),
+    (KeyPosition::CapsLock, 0x39),
+    (KeyPosition::AltLeft, 0x3A),
+    (KeyPosition::ControlLeft, 0x3B),
+    (KeyPosition::ShiftRight, 0x3C),
+    (KeyPosition::AltRight, 0x3D),
+    (KeyPosition::ControlRight, 0x3E),
+    (KeyPosition::ArrowLeft, 0x7B),
+    (KeyPosition::ArrowRight, 0x7C),
+    (KeyPosition::ArrowDown, 0x7D),
+    (KeyPosition::ArrowUp, 0x7E),
+];
+
+// wasm, android, ios and the rest scancodes
+// don't match either table
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+    target_os = "macos",
+)))]
+const SCANCODES: &[(KeyPosition, ScanCode)] = &[];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: &[KeyPosition] = &[
+        KeyPosition::Escape,
+        KeyPosition::Digit1,
+        KeyPosition::Digit2,
+        KeyPosition::Digit3,
+        KeyPosition::Digit4,
+        KeyPosition::Digit5,
+        KeyPosition::Digit6,
+        KeyPosition::Digit7,
+        KeyPosition::Digit8,
+        KeyPosition::Digit9,
+        KeyPosition::Digit0,
+        KeyPosition::Minus,
+        KeyPosition::Equal,
+        KeyPosition::Backspace,
+        KeyPosition::Tab,
+        KeyPosition::KeyQ,
+        KeyPosition::KeyW,
+        KeyPosition::KeyE,
+        KeyPosition::KeyR,
+        KeyPosition::KeyT,
+        KeyPosition::KeyY,
+        KeyPosition::KeyU,
+        KeyPosition::KeyI,
+        KeyPosition::KeyO,
+        KeyPosition::KeyP,
+        KeyPosition::BracketLeft,
+        KeyPosition::BracketRight,
+        KeyPosition::Enter,
+        KeyPosition::CapsLock,
+        KeyPosition::KeyA,
+        KeyPosition::KeyS,
+        KeyPosition::KeyD,
+        KeyPosition::KeyF,
+        KeyPosition::KeyG,
+        KeyPosition::KeyH,
+        KeyPosition::KeyJ,
+        KeyPosition::KeyK,
+        KeyPosition::KeyL,
+        KeyPosition::Semicolon,
+        KeyPosition::Quote,
+        KeyPosition::Backquote,
+        KeyPosition::Backslash,
+        KeyPosition::ShiftLeft,
+        KeyPosition::KeyZ,
+        KeyPosition::KeyX,
+        KeyPosition::KeyC,
+        KeyPosition::KeyV,
+        KeyPosition::KeyB,
+        KeyPosition::KeyN,
+        KeyPosition::KeyM,
+        KeyPosition::Comma,
+        KeyPosition::Period,
+        KeyPosition::Slash,
+        KeyPosition::ShiftRight,
+        KeyPosition::ControlLeft,
+        KeyPosition::AltLeft,
+        KeyPosition::Space,
+        KeyPosition::AltRight,
+        KeyPosition::ControlRight,
+        KeyPosition::ArrowUp,
+        KeyPosition::ArrowLeft,
+        KeyPosition::ArrowDown,
+        KeyPosition::ArrowRight,
+    ];
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(SCANCODES.len(), ALL.len());
+        for position in ALL {
+            let scancode = position.scancode().unwrap();
+            assert_eq!(KeyPosition::from_scancode(scancode), Some(*position));
+        }
+        assert_eq!(KeyPosition::from_scancode(0xFFFF), None);
+    }
+}
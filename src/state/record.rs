@@ -0,0 +1,330 @@
+use super::input_event::InputEvent;
+use crate::event::Event;
+use bincode::Options;
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+    time::Duration,
+};
+use winit::window::WindowId;
+
+//
+
+/// Records [`InputEvent`]s into a compact
+/// binary file for reproducing bugs
+///
+/// Feed it every event before the states
+/// and replay the file with [`InputPlayback`].
+///
+/// Window events of only one window are
+/// recorded, see [`InputRecorder::with_window`].
+///
+/// ```no_run
+/// # use main_game_loop::{event::Event, prelude::*};
+/// # use std::io::Write;
+/// // in the event loop
+/// fn event(recorder: &mut InputRecorder<impl Write>, keyboard: &mut KeyboardState, event: &Event) {
+///     recorder.event(event).unwrap();
+///     keyboard.event(event);
+/// }
+///
+/// let mut recorder = InputRecorder::create("input.rec").unwrap();
+/// let mut keyboard = KeyboardState::new().with_auto_clear();
+/// ```
+#[derive(Debug)]
+pub struct InputRecorder<W: Write> {
+    writer: W,
+    start: Instant,
+    tick: u64,
+
+    /// when set to `None`: it will take
+    /// the id of the first event with a
+    /// window id, like [`WindowState`](super::window::WindowState)
+    window: Option<WindowId>,
+}
+
+/// Replays a file written by [`InputRecorder`]
+///
+/// Events go to the `input_event` method
+/// of each state, per recorded frame or
+/// by the time since the recording started.
+///
+/// ```no_run
+/// # use main_game_loop::prelude::*;
+/// let mut playback = InputPlayback::load("input.rec").unwrap();
+/// let mut keyboard = KeyboardState::new().with_auto_clear();
+/// let mut window = WindowState::default();
+///
+/// // once per frame
+/// for recorded in playback.next_tick() {
+///     keyboard.input_event(&recorded.event);
+///     window.input_event(&recorded.event);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InputPlayback {
+    events: Vec<RecordedEvent>,
+    next: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// frames since the recording started,
+    /// counted by `RedrawEventsCleared`
+    pub tick: u64,
+
+    /// time since the recording started
+    pub time: Duration,
+
+    pub event: InputEvent,
+}
+
+//
+
+const MAGIC: &[u8; 8] = b"mglinput";
+const VERSION: u32 = 1;
+
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+fn invalid_data<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+impl InputRecorder<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> InputRecorder<W> {
+    /// Writes the file header right away
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            tick: 0,
+            window: None,
+        })
+    }
+
+    /// Record the window events of `window` only
+    pub fn with_window(mut self, window: WindowId) -> Self {
+        self.set_window(Some(window));
+        self
+    }
+
+    pub fn set_window(&mut self, window: Option<WindowId>) {
+        self.window = window;
+    }
+
+    /// Record `event` if any state reacts to it
+    ///
+    /// Playback skips the window id check of
+    /// [`WindowState::event`](super::window::WindowState::event),
+    /// so events of other windows are dropped here
+    pub fn event(&mut self, event: &Event) -> io::Result<()> {
+        if let Event::WindowEvent { window_id, .. } = event {
+            if *self.window.get_or_insert(*window_id) != *window_id {
+                return Ok(());
+            }
+        }
+
+        match InputEvent::from_event(event) {
+            Some(event) => self.record(event),
+            None => Ok(()),
+        }
+    }
+
+    pub fn record(&mut self, event: InputEvent) -> io::Result<()> {
+        let end_of_tick = event == InputEvent::RedrawEventsCleared;
+        let recorded = RecordedEvent {
+            tick: self.tick,
+            time: self.start.elapsed(),
+            event,
+        };
+        options()
+            .serialize_into(&mut self.writer, &recorded)
+            .map_err(invalid_data)?;
+
+        if end_of_tick {
+            self.tick += 1;
+        }
+        Ok(())
+    }
+
+    /// Frames recorded so far
+    #[inline]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl InputPlayback {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() < MAGIC.len() + 4 {
+            return Err(invalid_data("not an input recording"));
+        }
+        let (header, mut bytes) = bytes.split_at(MAGIC.len() + 4);
+        if &header[..MAGIC.len()] != MAGIC {
+            return Err(invalid_data("not an input recording"));
+        }
+        if header[MAGIC.len()..] != VERSION.to_le_bytes() {
+            return Err(invalid_data("unsupported input recording version"));
+        }
+
+        let mut events = Vec::new();
+        while !bytes.is_empty() {
+            let event = options()
+                .deserialize_from(&mut bytes)
+                .map_err(invalid_data)?;
+            events.push(event);
+        }
+
+        Ok(Self { events, next: 0 })
+    }
+
+    /// Events of the next recorded frame,
+    /// up to and including its
+    /// `RedrawEventsCleared`
+    pub fn next_tick(&mut self) -> &[RecordedEvent] {
+        let start = self.next;
+        let len = self.events[start..]
+            .iter()
+            .position(|recorded| recorded.event == InputEvent::RedrawEventsCleared)
+            .map_or(self.events.len() - start, |i| i + 1);
+        self.next += len;
+        &self.events[start..self.next]
+    }
+
+    /// Events recorded up to `time`
+    /// since the recording started
+    pub fn until(&mut self, time: Duration) -> &[RecordedEvent] {
+        let start = self.next;
+        let len = self.events[start..]
+            .iter()
+            .take_while(|recorded| recorded.time <= time)
+            .count();
+        self.next += len;
+        &self.events[start..self.next]
+    }
+
+    /// Every event was played
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.next == self.events.len()
+    }
+
+    /// Start over from the first event
+    #[inline]
+    pub fn rewind(&mut self) {
+        self.next = 0;
+    }
+
+    #[inline]
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::WindowEvent;
+
+    fn focused(window: u64, focused: bool) -> Event<'static> {
+        Event::WindowEvent {
+            window_id: WindowId::from(window),
+            event: WindowEvent::Focused(focused),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut recorder = InputRecorder::new(Vec::new()).unwrap();
+        recorder.event(&focused(1, true)).unwrap();
+        recorder.event(&Event::RedrawEventsCleared).unwrap();
+        recorder.event(&focused(1, false)).unwrap();
+        assert_eq!(recorder.tick(), 1);
+
+        let bytes = recorder.into_inner().unwrap();
+        let mut playback = InputPlayback::from_reader(&bytes[..]).unwrap();
+        let events = |recorded: &[RecordedEvent]| {
+            recorded
+                .iter()
+                .map(|recorded| (recorded.tick, recorded.event.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            events(playback.next_tick()),
+            [
+                (0, InputEvent::Focused(true)),
+                (0, InputEvent::RedrawEventsCleared)
+            ]
+        );
+        assert_eq!(
+            events(playback.next_tick()),
+            [(1, InputEvent::Focused(false))]
+        );
+        assert!(playback.is_finished());
+
+        playback.rewind();
+        assert_eq!(playback.until(Duration::MAX).len(), 3);
+    }
+
+    #[test]
+    fn other_windows_are_skipped() {
+        let mut recorder = InputRecorder::new(Vec::new()).unwrap();
+        recorder.event(&focused(1, true)).unwrap();
+        recorder.event(&focused(2, true)).unwrap();
+        recorder.event(&focused(1, false)).unwrap();
+
+        let bytes = recorder.into_inner().unwrap();
+        let playback = InputPlayback::from_reader(&bytes[..]).unwrap();
+        assert_eq!(playback.events().len(), 2);
+
+        let mut recorder = InputRecorder::new(Vec::new())
+            .unwrap()
+            .with_window(WindowId::from(2));
+        recorder.event(&focused(1, true)).unwrap();
+        recorder.event(&focused(2, false)).unwrap();
+
+        let bytes = recorder.into_inner().unwrap();
+        let playback = InputPlayback::from_reader(&bytes[..]).unwrap();
+        assert_eq!(playback.events().len(), 1);
+        assert_eq!(playback.events()[0].event, InputEvent::Focused(false));
+    }
+
+    #[test]
+    fn invalid_header() {
+        assert!(InputPlayback::from_reader(&b"mgl"[..]).is_err());
+        assert!(InputPlayback::from_reader(&b"notinput\x01\0\0\0"[..]).is_err());
+    }
+}
@@ -0,0 +1,399 @@
+use super::input_event::InputEvent;
+use crate::event::Event;
+use std::ops::Range;
+use winit::event::{ElementState, Ime, ModifiersState, VirtualKeyCode};
+
+//
+
+/// Editable single line text buffer
+///
+/// Fed by `ReceivedCharacter`, IME events
+/// and editing keys: backspace, delete,
+/// arrows, home, end and `ctrl+a`. Holding
+/// shift while moving extends the selection.
+///
+/// Call `Window::set_ime_allowed(true)`
+/// to receive IME events.
+///
+/// ```no_run
+/// # use main_game_loop::prelude::*;
+/// let mut chat = TextInputState::new().with_auto_clear();
+///
+/// // chat.event(&event);
+/// if chat.just_submitted() {
+///     let message = chat.take_text();
+///     // send(message);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TextInputState {
+    text: String,
+
+    /// byte index into `text`
+    cursor: usize,
+
+    /// other end of the selection
+    anchor: Option<usize>,
+
+    preedit: Option<Preedit>,
+
+    /// characters typed since the last clear
+    typed: String,
+    submitted: bool,
+
+    modifiers: ModifiersState,
+    auto_clear: bool,
+}
+
+/// IME composition in progress,
+/// not yet part of the text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preedit {
+    pub text: String,
+
+    /// byte range of the IME cursor in `text`
+    pub cursor: Option<(usize, usize)>,
+}
+
+//
+
+impl TextInputState {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_auto_clear(mut self) -> Self {
+        self.set_auto_clear(true);
+        self
+    }
+
+    #[inline]
+    pub fn set_auto_clear(&mut self, auto_clear: bool) {
+        self.auto_clear = auto_clear;
+    }
+
+    pub fn event(&mut self, event: &Event) {
+        if let Some(event) = InputEvent::from_event(event) {
+            self.input_event(&event);
+        }
+    }
+
+    pub fn input_event(&mut self, event: &InputEvent) {
+        match event {
+            // composed text comes from the commit
+            InputEvent::ReceivedCharacter(c) if self.preedit.is_none() && !c.is_control() => {
+                self.insert(&c.to_string())
+            }
+
+            InputEvent::Ime(ime) => match ime {
+                Ime::Enabled => {}
+                Ime::Preedit(text, _) if text.is_empty() => self.preedit = None,
+                Ime::Preedit(text, cursor) => {
+                    self.preedit = Some(Preedit {
+                        text: text.clone(),
+                        cursor: *cursor,
+                    })
+                }
+                Ime::Commit(text) => {
+                    self.preedit = None;
+                    self.insert(text);
+                }
+                Ime::Disabled => self.preedit = None,
+            },
+
+            InputEvent::Modifiers(modifiers) => self.modifiers = *modifiers,
+
+            // modifier releases are missed while unfocused
+            InputEvent::Focused(false) => self.modifiers = ModifiersState::empty(),
+
+            InputEvent::Key {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(key),
+                ..
+            } if self.preedit.is_none() => self.key(*key),
+
+            // auto clear event
+            InputEvent::RedrawEventsCleared if self.auto_clear => self.clear(),
+
+            _ => {}
+        }
+    }
+
+    fn key(&mut self, key: VirtualKeyCode) {
+        let select = self.modifiers.shift();
+        match key {
+            VirtualKeyCode::Back => self.backspace(),
+            VirtualKeyCode::Delete => self.delete(),
+            VirtualKeyCode::Left => self.move_left(select),
+            VirtualKeyCode::Right => self.move_right(select),
+            VirtualKeyCode::Home => self.move_to(0, select),
+            VirtualKeyCode::End => self.move_to(self.text.len(), select),
+            VirtualKeyCode::A if self.modifiers.ctrl() => self.select_all(),
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => self.submitted = true,
+            _ => {}
+        }
+    }
+
+    // buffer
+
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.text.len();
+        self.anchor = None;
+    }
+
+    /// Take the text, leaving the buffer empty
+    pub fn take_text(&mut self) -> String {
+        self.cursor = 0;
+        self.anchor = None;
+        std::mem::take(&mut self.text)
+    }
+
+    /// Cursor as a byte index into [`TextInputState::text`]
+    #[inline]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Current IME composition, shown
+    /// at the cursor but not in the text
+    #[inline]
+    pub fn preedit(&self) -> Option<&Preedit> {
+        self.preedit.as_ref()
+    }
+
+    /// Characters typed since the last clear
+    #[inline]
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    /// Enter was pressed since the last clear
+    #[inline]
+    pub fn just_submitted(&self) -> bool {
+        self.submitted
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.typed.clear();
+        self.submitted = false;
+    }
+
+    // selection
+
+    /// Selected byte range, never empty
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        let range = anchor.min(self.cursor)..anchor.max(self.cursor);
+        (!range.is_empty()).then_some(range)
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection().map(|range| &self.text[range])
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.text.len();
+    }
+
+    /// Select a byte range, clamped to
+    /// the text and char boundaries
+    pub fn select(&mut self, range: Range<usize>) {
+        self.anchor = Some(self.floor_boundary(range.start));
+        self.cursor = self.floor_boundary(range.end);
+    }
+
+    // editing
+
+    /// Insert at the cursor, replacing the selection
+    pub fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+        self.typed.push_str(text);
+    }
+
+    pub fn backspace(&mut self) {
+        if !self.delete_selection() {
+            let start = self.prev_boundary();
+            self.text.replace_range(start..self.cursor, "");
+            self.cursor = start;
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if !self.delete_selection() {
+            let end = self.next_boundary();
+            self.text.replace_range(self.cursor..end, "");
+        }
+    }
+
+    /// Returns true if there was a selection
+    pub fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        match selection {
+            Some(range) => {
+                self.cursor = range.start;
+                self.text.replace_range(range, "");
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn move_left(&mut self, select: bool) {
+        match self.selection() {
+            Some(range) if !select => self.move_to(range.start, false),
+            _ => self.move_to(self.prev_boundary(), select),
+        }
+    }
+
+    pub fn move_right(&mut self, select: bool) {
+        match self.selection() {
+            Some(range) if !select => self.move_to(range.end, false),
+            _ => self.move_to(self.next_boundary(), select),
+        }
+    }
+
+    /// Move the cursor to a byte index, extending
+    /// the selection if `select` is true
+    pub fn move_to(&mut self, index: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = self.floor_boundary(index);
+    }
+
+    //
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .chars()
+            .next_back()
+            .map_or(self.cursor, |c| self.cursor - c.len_utf8())
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    fn floor_boundary(&self, index: usize) -> usize {
+        let mut index = index.min(self.text.len());
+        while !self.text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &mut TextInputState, s: &str) {
+        for c in s.chars() {
+            text.input_event(&InputEvent::ReceivedCharacter(c));
+        }
+    }
+
+    fn key(text: &mut TextInputState, key: VirtualKeyCode) {
+        text.input_event(&InputEvent::Key {
+            state: ElementState::Pressed,
+            scancode: 0,
+            virtual_keycode: Some(key),
+        });
+    }
+
+    #[test]
+    fn characters_with_ime_enabled() {
+        let mut text = TextInputState::new();
+        text.input_event(&InputEvent::Ime(Ime::Enabled));
+        typed(&mut text, "hi");
+        assert_eq!(text.text(), "hi");
+        assert_eq!(text.typed(), "hi");
+    }
+
+    #[test]
+    fn characters_ignored_during_preedit() {
+        let mut text = TextInputState::new();
+        text.input_event(&InputEvent::Ime(Ime::Enabled));
+        text.input_event(&InputEvent::Ime(Ime::Preedit("に".into(), Some((0, 3)))));
+        typed(&mut text, "n");
+        key(&mut text, VirtualKeyCode::Back);
+        assert_eq!(text.text(), "");
+        assert_eq!(text.preedit().unwrap().text, "に");
+
+        text.input_event(&InputEvent::Ime(Ime::Commit("日本".into())));
+        assert_eq!(text.preedit(), None);
+        assert_eq!(text.text(), "日本");
+        assert_eq!(text.cursor(), 6);
+    }
+
+    #[test]
+    fn multibyte_editing() {
+        let mut text = TextInputState::new();
+        text.set_text("añ日b");
+        assert_eq!(text.cursor(), 7);
+
+        text.move_left(false);
+        text.backspace();
+        assert_eq!(text.text(), "añb");
+        assert_eq!(text.cursor(), 3);
+
+        text.move_left(false);
+        text.delete();
+        assert_eq!(text.text(), "ab");
+        assert_eq!(text.cursor(), 1);
+
+        // indices inside a char are floored
+        text.set_text("日本語");
+        text.select(1..5);
+        assert_eq!(text.selection(), Some(0..3));
+        assert_eq!(text.selected_text(), Some("日"));
+
+        text.move_to(text.text().len(), true);
+        text.insert("x");
+        assert_eq!(text.text(), "x");
+    }
+
+    #[test]
+    fn selection_keys() {
+        let mut text = TextInputState::new();
+        typed(&mut text, "ab");
+        text.input_event(&InputEvent::Modifiers(ModifiersState::SHIFT));
+        key(&mut text, VirtualKeyCode::Left);
+        assert_eq!(text.selected_text(), Some("b"));
+
+        text.input_event(&InputEvent::Modifiers(ModifiersState::empty()));
+        key(&mut text, VirtualKeyCode::Left);
+        assert_eq!(text.selection(), None);
+        assert_eq!(text.cursor(), 1);
+    }
+
+    #[test]
+    fn focus_loss_resets_modifiers() {
+        let mut text = TextInputState::new();
+        typed(&mut text, "ab");
+        text.input_event(&InputEvent::Modifiers(ModifiersState::CTRL));
+        text.input_event(&InputEvent::Focused(false));
+
+        // ctrl was released elsewhere, this is a plain `a`
+        key(&mut text, VirtualKeyCode::A);
+        assert_eq!(text.selection(), None);
+    }
+}
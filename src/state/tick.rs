@@ -0,0 +1,104 @@
+use super::{
+    action::ActionState, gamepad::GamepadState, input::Input, keyboard::KeyboardState,
+    mouse::MouseState, text::TextInputState, touch::TouchState,
+};
+use instant::Instant;
+use std::hash::Hash;
+
+//
+
+/// Input state read by fixed step updates
+///
+/// Without `auto_clear`, just pressed and
+/// just released edges are latched until
+/// [`TickInput::end_tick`], so a frame with
+/// no updates doesn't miss them and a frame
+/// with several doesn't handle them twice.
+///
+/// See [`crate::UpdateLoop::update_with`].
+pub trait TickInput {
+    /// Clear the edges seen by the last tick
+    fn end_tick(&mut self);
+}
+
+//
+
+impl<T> TickInput for Input<T>
+where
+    T: Copy + Eq + Hash,
+{
+    fn end_tick(&mut self) {
+        self.clear();
+    }
+}
+
+impl<A> TickInput for ActionState<A>
+where
+    A: Copy + Eq + Hash,
+{
+    fn end_tick(&mut self) {
+        self.clear();
+    }
+}
+
+impl TickInput for KeyboardState {
+    fn end_tick(&mut self) {
+        self.clear();
+    }
+}
+
+impl TickInput for GamepadState {
+    fn end_tick(&mut self) {
+        self.clear();
+    }
+}
+
+impl TickInput for MouseState {
+    fn end_tick(&mut self) {
+        self.clear();
+    }
+}
+
+impl TickInput for TextInputState {
+    fn end_tick(&mut self) {
+        self.clear();
+    }
+}
+
+impl TickInput for TouchState {
+    fn end_tick(&mut self) {
+        self.clear();
+        self.update_at(Instant::now());
+    }
+}
+
+impl<T> TickInput for &mut T
+where
+    T: TickInput + ?Sized,
+{
+    fn end_tick(&mut self) {
+        (**self).end_tick();
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($t:ident),+) => {
+        impl<$($t),+> TickInput for ($($t,)+)
+        where
+            $($t: TickInput),+
+        {
+            #[allow(non_snake_case)]
+            fn end_tick(&mut self) {
+                let ($($t,)+) = self;
+                $($t.end_tick();)+
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
@@ -0,0 +1,504 @@
+use super::input_event::InputEvent;
+use crate::event::Event;
+use glam::Vec2;
+use instant::Instant;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{f32::consts::PI, time::Duration};
+
+//
+
+pub use winit::event::TouchPhase;
+
+//
+
+/// Active touch points and gestures
+///
+/// Tap, long-press and swipe are single
+/// finger gestures, pinch and rotate need
+/// exactly two fingers. Feed synthesized
+/// touches with [`TouchState::touch_at`].
+///
+/// ```no_run
+/// # use main_game_loop::prelude::*;
+/// let mut touch = TouchState::new().with_auto_clear();
+///
+/// // touch.event(&event);
+/// for gesture in touch.gestures() {
+///     if let Gesture::Tap { position } = gesture {
+///         // select(position);
+///     }
+/// }
+/// // zoom *= touch.pinch_scale();
+/// ```
+#[derive(Debug, Clone)]
+pub struct TouchState {
+    touches: FxHashMap<u64, TouchPoint>,
+    ended: Vec<TouchPoint>,
+    gestures: Vec<Gesture>,
+    config: GestureConfig,
+
+    /// touches that already reported a long-press
+    long_pressed: FxHashSet<u64>,
+
+    /// more than one finger touched since the
+    /// screen was last empty, no single finger
+    /// gestures until then
+    multi: bool,
+
+    /// distance and angle between the two fingers
+    pair: Option<(f32, f32)>,
+    pinch_scale: f32,
+    rotation: f32,
+
+    auto_clear: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchPoint {
+    pub id: u64,
+    pub phase: TouchPhase,
+
+    /// position in physical pixels
+    pub start: Vec2,
+    pub position: Vec2,
+    pub started_at: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Tap {
+        position: Vec2,
+    },
+    LongPress {
+        position: Vec2,
+    },
+    Swipe {
+        direction: SwipeDirection,
+        start: Vec2,
+        end: Vec2,
+
+        /// pixels per second
+        velocity: Vec2,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Gesture recognizer thresholds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureConfig {
+    /// max movement in pixels for
+    /// taps and long-presses
+    pub tap_distance: f32,
+    pub tap_duration: Duration,
+    pub long_press_duration: Duration,
+    pub swipe_distance: f32,
+    pub swipe_duration: Duration,
+}
+
+//
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            tap_distance: 10.0,
+            tap_duration: Duration::from_millis(300),
+            long_press_duration: Duration::from_millis(500),
+            swipe_distance: 50.0,
+            swipe_duration: Duration::from_millis(500),
+        }
+    }
+}
+
+impl Default for TouchState {
+    fn default() -> Self {
+        Self {
+            touches: Default::default(),
+            ended: Default::default(),
+            gestures: Default::default(),
+            config: Default::default(),
+            long_pressed: Default::default(),
+            multi: false,
+            pair: None,
+            pinch_scale: 1.0,
+            rotation: 0.0,
+            auto_clear: false,
+        }
+    }
+}
+
+impl TouchPoint {
+    /// Movement since the touch started
+    #[inline]
+    pub fn delta(&self) -> Vec2 {
+        self.position - self.start
+    }
+}
+
+impl TouchState {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_auto_clear(mut self) -> Self {
+        self.set_auto_clear(true);
+        self
+    }
+
+    #[inline]
+    pub fn set_auto_clear(&mut self, auto_clear: bool) {
+        self.auto_clear = auto_clear;
+    }
+
+    #[inline]
+    pub fn with_config(mut self, config: GestureConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    #[inline]
+    pub fn config_mut(&mut self) -> &mut GestureConfig {
+        &mut self.config
+    }
+
+    pub fn event(&mut self, event: &Event) {
+        if let Some(event) = InputEvent::from_event(event) {
+            self.input_event(&event);
+        }
+    }
+
+    pub fn input_event(&mut self, event: &InputEvent) {
+        match *event {
+            // touch events
+            InputEvent::Touch { id, phase, x, y } => {
+                self.touch_at(id, phase, Vec2::new(x as _, y as _), Instant::now())
+            }
+
+            // auto clear event
+            InputEvent::RedrawEventsCleared if self.auto_clear => {
+                self.clear();
+                self.update_at(Instant::now());
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Handle a touch event that happened at `now`
+    pub fn touch_at(&mut self, id: u64, phase: TouchPhase, position: Vec2, now: Instant) {
+        match phase {
+            TouchPhase::Started => {
+                self.touches.insert(
+                    id,
+                    TouchPoint {
+                        id,
+                        phase,
+                        start: position,
+                        position,
+                        started_at: now,
+                    },
+                );
+                self.multi |= self.touches.len() > 1;
+                self.pair = self.pair();
+            }
+            TouchPhase::Moved => {
+                if let Some(touch) = self.touches.get_mut(&id) {
+                    touch.phase = phase;
+                    touch.position = position;
+                }
+
+                if let (Some((distance, angle)), Some((new_distance, new_angle))) =
+                    (self.pair, self.pair())
+                {
+                    if distance > 0.0 {
+                        self.pinch_scale *= new_distance / distance;
+                    }
+                    self.rotation += wrap_angle(new_angle - angle);
+                    self.pair = Some((new_distance, new_angle));
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if let Some(mut touch) = self.touches.remove(&id) {
+                    touch.phase = phase;
+                    touch.position = position;
+                    if phase == TouchPhase::Ended {
+                        self.recognize(&touch, now);
+                    }
+                    self.ended.push(touch);
+                }
+
+                self.long_pressed.remove(&id);
+                self.pair = self.pair();
+                if self.touches.is_empty() {
+                    self.multi = false;
+                }
+            }
+        }
+
+        self.update_at(now);
+    }
+
+    /// Report long-presses of fingers that
+    /// haven't moved or sent events
+    pub fn update_at(&mut self, now: Instant) {
+        if self.multi {
+            return;
+        }
+
+        for touch in self.touches.values() {
+            if now.duration_since(touch.started_at) >= self.config.long_press_duration
+                && touch.delta().length() <= self.config.tap_distance
+                && self.long_pressed.insert(touch.id)
+            {
+                self.gestures.push(Gesture::LongPress {
+                    position: touch.position,
+                });
+            }
+        }
+    }
+
+    fn recognize(&mut self, touch: &TouchPoint, now: Instant) {
+        if self.multi || self.long_pressed.contains(&touch.id) {
+            return;
+        }
+
+        let duration = now.duration_since(touch.started_at);
+        let delta = touch.delta();
+        let distance = delta.length();
+
+        if duration <= self.config.tap_duration && distance <= self.config.tap_distance {
+            self.gestures.push(Gesture::Tap {
+                position: touch.position,
+            });
+        } else if duration <= self.config.swipe_duration && distance >= self.config.swipe_distance {
+            // positive y is down in window coordinates
+            let direction = if delta.x.abs() >= delta.y.abs() {
+                if delta.x > 0.0 {
+                    SwipeDirection::Right
+                } else {
+                    SwipeDirection::Left
+                }
+            } else if delta.y > 0.0 {
+                SwipeDirection::Down
+            } else {
+                SwipeDirection::Up
+            };
+
+            self.gestures.push(Gesture::Swipe {
+                direction,
+                start: touch.start,
+                end: touch.position,
+                velocity: delta / duration.as_secs_f32().max(f32::EPSILON),
+            });
+        }
+    }
+
+    /// Distance and angle between exactly two fingers
+    fn pair(&self) -> Option<(f32, f32)> {
+        if self.touches.len() != 2 {
+            return None;
+        }
+        let mut touches = self.touches.values();
+        let (a, b) = (touches.next()?, touches.next()?);
+        let (a, b) = if a.id < b.id { (a, b) } else { (b, a) };
+        let between = b.position - a.position;
+        Some((between.length(), between.y.atan2(between.x)))
+    }
+
+    // touches
+
+    pub fn get(&self, id: u64) -> Option<&TouchPoint> {
+        self.touches.get(&id)
+    }
+
+    /// Fingers currently on the screen
+    pub fn iter(&self) -> impl Iterator<Item = &TouchPoint> {
+        self.touches.values()
+    }
+
+    /// Touches that ended or were
+    /// cancelled since the last clear
+    pub fn iter_ended(&self) -> impl Iterator<Item = &TouchPoint> {
+        self.ended.iter()
+    }
+
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.touches.len()
+    }
+
+    // gestures
+
+    /// Gestures recognized since the last clear
+    #[inline]
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.gestures
+    }
+
+    /// Two finger zoom factor since the last
+    /// clear, above `1.0` when spreading
+    #[inline]
+    pub fn pinch_scale(&self) -> f32 {
+        self.pinch_scale
+    }
+
+    /// Two finger rotation in radians since
+    /// the last clear, clockwise on screen
+    #[inline]
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Midpoint of the two fingers
+    pub fn pinch_center(&self) -> Option<Vec2> {
+        if self.touches.len() != 2 {
+            return None;
+        }
+        let sum: Vec2 = self.touches.values().map(|touch| touch.position).sum();
+        Some(sum / 2.0)
+    }
+
+    /// Clear ended touches, gestures,
+    /// pinch scale and rotation
+    pub fn clear(&mut self) {
+        self.ended.clear();
+        self.gestures.clear();
+        self.pinch_scale = 1.0;
+        self.rotation = 0.0;
+    }
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn tap() {
+        let mut touch = TouchState::new();
+        let now = Instant::now();
+        touch.touch_at(0, TouchPhase::Started, Vec2::new(5.0, 5.0), now);
+        touch.touch_at(0, TouchPhase::Moved, Vec2::new(7.0, 5.0), now + ms(50));
+        touch.touch_at(0, TouchPhase::Ended, Vec2::new(7.0, 5.0), now + ms(100));
+
+        assert_eq!(
+            touch.gestures(),
+            [Gesture::Tap {
+                position: Vec2::new(7.0, 5.0)
+            }]
+        );
+        assert_eq!(touch.count(), 0);
+        assert_eq!(touch.iter_ended().count(), 1);
+
+        touch.clear();
+        assert!(touch.gestures().is_empty());
+        assert_eq!(touch.iter_ended().count(), 0);
+    }
+
+    #[test]
+    fn long_press() {
+        let mut touch = TouchState::new();
+        let now = Instant::now();
+        touch.touch_at(0, TouchPhase::Started, Vec2::ZERO, now);
+
+        touch.update_at(now + ms(400));
+        assert!(touch.gestures().is_empty());
+
+        touch.update_at(now + ms(600));
+        touch.update_at(now + ms(700));
+        assert_eq!(
+            touch.gestures(),
+            [Gesture::LongPress {
+                position: Vec2::ZERO
+            }]
+        );
+
+        // no tap after a long-press
+        touch.clear();
+        touch.touch_at(0, TouchPhase::Ended, Vec2::ZERO, now + ms(800));
+        assert!(touch.gestures().is_empty());
+    }
+
+    #[test]
+    fn swipe_direction() {
+        let swipe = |end: Vec2| {
+            let mut touch = TouchState::new();
+            let now = Instant::now();
+            touch.touch_at(0, TouchPhase::Started, Vec2::ZERO, now);
+            touch.touch_at(0, TouchPhase::Ended, end, now + ms(200));
+            match touch.gestures() {
+                [Gesture::Swipe {
+                    direction,
+                    velocity,
+                    ..
+                }] => {
+                    assert!((*velocity - end * 5.0).length() < 1e-3);
+                    *direction
+                }
+                other => panic!("not a swipe: {other:?}"),
+            }
+        };
+
+        assert_eq!(swipe(Vec2::new(100.0, 20.0)), SwipeDirection::Right);
+        assert_eq!(swipe(Vec2::new(-100.0, 20.0)), SwipeDirection::Left);
+        assert_eq!(swipe(Vec2::new(20.0, 100.0)), SwipeDirection::Down);
+        assert_eq!(swipe(Vec2::new(20.0, -100.0)), SwipeDirection::Up);
+    }
+
+    #[test]
+    fn pinch_and_rotate() {
+        let mut touch = TouchState::new();
+        let now = Instant::now();
+        touch.touch_at(0, TouchPhase::Started, Vec2::ZERO, now);
+        touch.touch_at(1, TouchPhase::Started, Vec2::new(10.0, 0.0), now);
+        assert_eq!(touch.pinch_center(), Some(Vec2::new(5.0, 0.0)));
+
+        touch.touch_at(1, TouchPhase::Moved, Vec2::new(20.0, 0.0), now);
+        assert!((touch.pinch_scale() - 2.0).abs() < 1e-5);
+        assert_eq!(touch.rotation(), 0.0);
+
+        // positive y is down, so this is clockwise on screen
+        touch.touch_at(1, TouchPhase::Moved, Vec2::new(0.0, 20.0), now);
+        assert!((touch.rotation() - PI / 2.0).abs() < 1e-5);
+
+        touch.touch_at(1, TouchPhase::Moved, Vec2::new(20.0, 0.0), now);
+        assert!(touch.rotation().abs() < 1e-5);
+
+        // no single finger gestures after a pinch
+        touch.touch_at(1, TouchPhase::Ended, Vec2::new(20.0, 0.0), now);
+        touch.touch_at(0, TouchPhase::Ended, Vec2::ZERO, now);
+        assert!(touch.gestures().is_empty());
+    }
+
+    #[test]
+    fn end_tick_reports_long_press() {
+        use crate::state::tick::TickInput;
+
+        // end_tick reads the clock, so wait out a short long press
+        let mut touch = TouchState::new().with_config(GestureConfig {
+            long_press_duration: ms(10),
+            ..Default::default()
+        });
+        touch.touch_at(0, TouchPhase::Started, Vec2::ZERO, Instant::now());
+        assert!(touch.gestures().is_empty());
+        std::thread::sleep(ms(10));
+
+        touch.end_tick();
+        assert_eq!(touch.gestures().len(), 1);
+    }
+}
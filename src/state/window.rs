@@ -1,7 +1,7 @@
+use super::input_event::InputEvent;
 use crate::event::Event;
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::WindowEvent,
     window::{Window, WindowId},
 };
 
@@ -59,42 +59,31 @@ impl WindowState {
             return;
         }
 
-        match event {
-            Event::WindowEvent {
-                event: WindowEvent::CursorEntered { .. },
-                ..
-            } => {
+        if let Some(event) = InputEvent::from_event(event) {
+            self.input_event(&event);
+        }
+    }
+
+    /// Handle an event of this window
+    pub fn input_event(&mut self, event: &InputEvent) {
+        match *event {
+            InputEvent::CursorEntered => {
                 self.cursor_in = true;
             }
-            Event::WindowEvent {
-                event: WindowEvent::CursorLeft { .. },
-                ..
-            } => {
+            InputEvent::CursorLeft => {
                 self.cursor_in = false;
             }
-            Event::WindowEvent {
-                event: WindowEvent::CursorMoved { position, .. },
-                ..
-            } => {
-                self.cursor_pos = *position;
+            InputEvent::CursorMoved { x, y } => {
+                self.cursor_pos = PhysicalPosition::new(x, y);
             }
-            Event::WindowEvent {
-                event: WindowEvent::Resized(size),
-                ..
-            } => {
-                self.size = *size;
-                self.aspect = Self::aspect(*size);
+            InputEvent::Resized { width, height } => {
+                self.size = PhysicalSize::new(width, height);
+                self.aspect = Self::aspect(self.size);
             }
-            Event::WindowEvent {
-                event: WindowEvent::Focused(focused),
-                ..
-            } => {
-                self.focused = *focused;
+            InputEvent::Focused(focused) => {
+                self.focused = focused;
             }
-            Event::WindowEvent {
-                event: WindowEvent::CloseRequested,
-                ..
-            } => self.should_close = true,
+            InputEvent::CloseRequested => self.should_close = true,
             _ => {}
         }
     }
@@ -1,3 +1,4 @@
+use crate::state::tick::TickInput;
 use instant::Instant;
 use std::time::Duration;
 
@@ -17,6 +18,23 @@ pub struct UpdateLoop {
     interval: Duration,
     previous: Instant,
     lag: Duration,
+    stats: UpdateStats,
+}
+
+/// How well an [`UpdateLoop`] keeps up
+/// with its update rate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UpdateStats {
+    pub updates: u64,
+    pub frames: u64,
+
+    /// frames with no updates
+    pub idle_frames: u64,
+
+    /// frames with more than one update
+    pub catch_up_frames: u64,
+
+    pub max_updates_per_frame: u32,
 }
 
 //
@@ -49,6 +67,7 @@ impl UpdateLoop {
             interval: rate.to_interval(),
             previous: Instant::now(),
             lag: Duration::from_secs_f64(0.0),
+            stats: UpdateStats::default(),
         }
     }
 
@@ -83,6 +102,45 @@ impl UpdateLoop {
         updates.finish(self)
     }
 
+    /// [`UpdateLoop::update`] with tick aligned input
+    ///
+    /// Each update sees the input edges latched
+    /// since the previous update, they are cleared
+    /// after every update. Turn `auto_clear` off
+    /// for the states in `input`.
+    ///
+    /// ```no_run
+    /// # use main_game_loop::{
+    /// #     state::{keyboard::KeyboardState, mouse::MouseState},
+    /// #     update::UpdateLoop,
+    /// #     winit::event::{MouseButton, VirtualKeyCode},
+    /// # };
+    /// # fn jump() {}
+    /// # fn shoot() {}
+    /// # let mut update_loop = UpdateLoop::default();
+    /// let mut keyboard = KeyboardState::new();
+    /// let mut mouse = MouseState::new();
+    ///
+    /// // in the event loop
+    /// let delta = update_loop.update_with(&mut (&mut keyboard, &mut mouse), |(keyboard, mouse)| {
+    ///     if keyboard.just_pressed(VirtualKeyCode::Space) {
+    ///         jump();
+    ///     }
+    ///     if mouse.just_pressed(MouseButton::Left) {
+    ///         shoot();
+    ///     }
+    /// });
+    /// ```
+    pub fn update_with<I, F>(&mut self, input: &mut I, f: F) -> f32
+    where
+        I: TickInput,
+        F: FnMut(&I),
+    {
+        let updates = self.begin_updates();
+        updates.for_each_tick(input, f);
+        updates.finish(self)
+    }
+
     pub fn begin_updates(&mut self) -> UpdateGuard {
         UpdateGuard::new(self)
     }
@@ -96,18 +154,37 @@ impl UpdateLoop {
     pub fn will_update(&self) -> bool {
         self.lag + self.previous.elapsed() >= self.interval
     }
+
+    #[inline]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Statistics since the loop was
+    /// created or [`Self::reset_stats`]
+    #[inline]
+    pub fn stats(&self) -> UpdateStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = UpdateStats::default();
+    }
 }
 
 /// update count calculator
 ///
-/// ´´´no_run
+/// ```no_run
+/// # use main_game_loop::update::UpdateLoop;
+/// # fn update() {}
+/// # let mut update_loop = UpdateLoop::default();
 /// let updates = update_loop.begin_updates();
 /// for _ in 0..updates.count() {
 ///     update();
 /// }
 /// // dont forget to finish!
-/// updates.finish(&mut update_loop)
-/// ´´´
+/// let delta = updates.finish(&mut update_loop);
+/// ```
 pub struct UpdateGuard {
     previous: Instant,
     elapsed: Duration,
@@ -139,12 +216,58 @@ impl UpdateGuard {
         self.count
     }
 
+    /// run `f` [`Self::count`] times, ending
+    /// the input tick after each run
+    ///
+    /// See [`UpdateLoop::update_with`]
+    pub fn for_each_tick<I, F>(&self, input: &mut I, mut f: F)
+    where
+        I: TickInput,
+        F: FnMut(&I),
+    {
+        for _ in 0..self.count {
+            f(input);
+            input.end_tick();
+        }
+    }
+
     /// update the update loop
     pub fn finish(self, l: &mut UpdateLoop) -> f32 {
         l.previous = self.previous;
         l.lag += self.elapsed;
 
-        l.lag -= l.interval * self.count() as u32;
+        l.lag -= l.interval * self.count();
+
+        let stats = &mut l.stats;
+        stats.updates += self.count as u64;
+        stats.frames += 1;
+        stats.idle_frames += (self.count == 0) as u64;
+        stats.catch_up_frames += (self.count > 1) as u64;
+        stats.max_updates_per_frame = stats.max_updates_per_frame.max(self.count);
+
         l.delta()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::keyboard::KeyboardState;
+
+    #[test]
+    fn for_each_tick_ends_ticks() {
+        let mut update_loop = UpdateLoop::new(UpdateRate::Interval(Duration::from_millis(1)));
+        std::thread::sleep(Duration::from_millis(5));
+
+        let mut keyboard = KeyboardState::new();
+        let updates = update_loop.begin_updates();
+        let mut ticks = 0;
+        updates.for_each_tick(&mut keyboard, |_| ticks += 1);
+        assert_eq!(ticks, updates.count());
+        assert!(ticks >= 5);
+
+        updates.finish(&mut update_loop);
+        assert_eq!(update_loop.stats().updates, ticks as u64);
+        assert_eq!(update_loop.stats().frames, 1);
+    }
+}